@@ -1,5 +1,14 @@
 use std::fs;
-use std::convert::TryInto;
+use std::thread;
+
+use intcode::{ Computer, Value };
+use intcode::symbolic::{ self, Symbol, Unknown };
+
+// The noun and verb are patched into the program before running it, and the result is read back
+// once it halts
+const NOUN: usize = 1;
+const VERB: usize = 2;
+const RESULT: usize = 0;
 
 fn main() {
     let filename = "input";
@@ -11,154 +20,90 @@ fn main() {
 }
 
 fn process(input: String) {
-    let program: Vec<i32> = input.split(",")
-        .map(|x| x.trim().parse::<i32>().unwrap())
-        .collect();
+    let program = intcode::parse_program(&input)
+        .unwrap_or_else(|error| panic!("Failed to parse program: {}", error));
 
     let target = 19690720;
 
     println!("Looking for combination of noun and verb...");
 
-    for noun in 0..99 {
-        for verb in 0..99 {
-            let mut computer = Computer::new(program.clone(), noun, verb);
-            computer.run();
-
-            let result = computer.result();
-
-            if result == target {
-                println!("Success!");
-                println!("Noun: {} Verb: {} Result: {}", noun, verb, result);
-                return
-            }
-        }
-    }
-
-    println!("Failed to find a combination of noun and verb that yields {}", target);
-}
-
-fn to_address(value: i32) -> usize {
-    value.try_into().unwrap()
-}
+    let unknowns: Vec<_> = [NOUN, VERB].iter()
+        .map(|&address| Unknown { symbol: Symbol::Cell(address), values: 0..=99 })
+        .collect();
+    let workers = thread::available_parallelism().map_or(1, |workers| workers.get());
 
-struct OpcodeAdd {
-    lhs_address: usize,
-    rhs_address: usize,
-    result_address: usize,
-}
+    let solution = symbolic::solve(&program, &unknowns, RESULT, target, workers)
+        .unwrap_or_else(|error| panic!("Failed to solve for noun and verb: {}", error));
 
-impl OpcodeAdd {
-    fn new(computer: &Computer) -> OpcodeAdd {
-        assert!(computer.memory.len() >= computer.counter + 3); // 3 arguments
+    match solution {
+        Some(solution) => {
+            let (noun, verb) = (solution.values[0], solution.values[1]);
 
-        OpcodeAdd {
-            lhs_address: to_address(computer.memory[computer.counter + 1]),
-            rhs_address: to_address(computer.memory[computer.counter + 2]),
-            result_address: to_address(computer.memory[computer.counter + 3]),
+            println!("Success! (solved with {:?})", solution.method);
+            println!("Noun: {} Verb: {} Result: {}", noun, verb, run(&program, noun, verb));
         }
+        None => println!("Failed to find a combination of noun and verb that yields {}", target),
     }
-
-    fn exec(&self, computer: &mut Computer) {
-        computer.memory[self.result_address] = computer.memory[self.lhs_address] + computer.memory[self.rhs_address];
-    }
-}
-
-struct OpcodeMul {
-    lhs_address: usize,
-    rhs_address: usize,
-    result_address: usize,
 }
 
-impl OpcodeMul {
-    fn new(computer: &Computer) -> OpcodeMul {
-        assert!(computer.memory.len() >= computer.counter + 3); // 3 arguments
+fn run(program: &[Value], noun: Value, verb: Value) -> Value {
+    let mut computer = Computer::new(program.to_vec(), None);
+    computer.patch(NOUN, noun);
+    computer.patch(VERB, verb);
 
-        OpcodeMul {
-            lhs_address: to_address(computer.memory[computer.counter + 1]),
-            rhs_address: to_address(computer.memory[computer.counter + 2]),
-            result_address: to_address(computer.memory[computer.counter + 3]),
-        }
-    }
-
-    fn exec(&self, computer: &mut Computer) {
-        computer.memory[self.result_address] = computer.memory[self.lhs_address] * computer.memory[self.rhs_address];
-    }
-}
+    computer.run().unwrap_or_else(|error| panic!("Program failed: {}", error));
 
-struct Computer {
-    memory: Vec<i32>,
-    counter: usize,
-}
-
-impl Computer {
-    fn new(mut program: Vec<i32>, noun: i32, verb: i32) -> Computer {
-        program[1] = noun;
-        program[2] = verb;
-
-        Computer {
-            memory: program,
-            counter: 0,
-        }
-    }
-
-    fn run(&mut self) {
-        while self.memory[self.counter] != 99 {
-            self.step();
-        }
-    }
-
-    fn step(&mut self) {
-        let opcode = self.memory[self.counter];
-        if opcode == 1 {
-            OpcodeAdd::new(self).exec(self)
-        } else if opcode == 2 {
-            OpcodeMul::new(self).exec(self)
-        } else {
-            panic!("Unknown opcode: '{}'", opcode);
-        }
-
-        self.counter += 4;
-    }
-
-    fn result(&self) -> i32 {
-        assert!(self.memory[self.counter] == 99);
-        self.memory[0]
-    }
+    computer.read(RESULT)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn memory(computer: &Computer, size: usize) -> Vec<Value> {
+        (0..size).map(|address| computer.read(address)).collect()
+    }
+
     #[test]
     fn test_simple_add_opcode() {
-        let mut computer = Computer { memory: [1,0,0,0].to_vec(),  counter: 0 };
-        computer.step();
+        let mut computer = Computer::new([1,0,0,0].to_vec(), None);
+        computer.step().unwrap();
 
-        assert_eq!(computer.memory, [2,0,0,0].to_vec());
-        assert_eq!(computer.counter, 4);
+        assert_eq!(memory(&computer, 4), [2,0,0,0].to_vec());
+        assert_eq!(computer.program_counter(), 4);
     }
 
     #[test]
     fn test_simple_mul_opcode() {
-        let mut computer = Computer { memory: [2,3,0,3].to_vec(),  counter: 0 };
-        computer.step();
+        let mut computer = Computer::new([2,3,0,3].to_vec(), None);
+        computer.step().unwrap();
 
-        assert_eq!(computer.memory, [2,3,0,6].to_vec());
-        assert_eq!(computer.counter, 4);
+        assert_eq!(memory(&computer, 4), [2,3,0,6].to_vec());
+        assert_eq!(computer.program_counter(), 4);
     }
 
     #[test]
     fn test_complex_add_program() {
-        let mut computer = Computer::new([1,1,1,4,99,5,6,0,99].to_vec(), 1, 1);
-        computer.run();
-        assert_eq!(computer.memory, [30,1,1,4,2,5,6,0,99].to_vec());
+        let mut computer = Computer::new([1,1,1,4,99,5,6,0,99].to_vec(), None);
+        computer.patch(NOUN, 1);
+        computer.patch(VERB, 1);
+        computer.run().unwrap();
+
+        assert_eq!(memory(&computer, 9), [30,1,1,4,2,5,6,0,99].to_vec());
     }
 
     #[test]
     fn test_complex_mul_program() {
-        let mut computer = Computer::new([2,4,4,5,99,0].to_vec(), 4, 4);
-        computer.run();
-        assert_eq!(computer.memory, [2,4,4,5,99,9801].to_vec());
+        let mut computer = Computer::new([2,4,4,5,99,0].to_vec(), None);
+        computer.patch(NOUN, 4);
+        computer.patch(VERB, 4);
+        computer.run().unwrap();
+
+        assert_eq!(memory(&computer, 6), [2,4,4,5,99,9801].to_vec());
+    }
+
+    #[test]
+    fn test_result() {
+        assert_eq!(run(&[1,0,0,3,2,3,11,0,99,30,40,50], 9, 10), 3500);
     }
 }
@@ -1,7 +1,7 @@
-mod computer;
-
 use std::fs;
 
+use intcode::*;
+
 fn main() {
     let filename = "input";
     let contents = fs::read_to_string(filename).unwrap_or_else(
@@ -12,10 +12,11 @@ fn main() {
 }
 
 fn process(input: String) {
-    let program: Vec<i32> = input.split(",")
-        .map(|x| x.trim().parse::<i32>().unwrap())
-        .collect();
+    let program = intcode::parse_program(&input)
+        .unwrap_or_else(|error| panic!("Failed to parse program: {}", error));
 
-    let mut computer = computer::Computer::new_with_terminal(program);
-    computer.run();
+    let mut computer = Computer::new(program, None);
+    computer
+        .run()
+        .unwrap_or_else(|error| panic!("Program failed: {}", error));
 }
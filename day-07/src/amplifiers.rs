@@ -1,125 +1,117 @@
-use std::thread;
-use std::sync::mpsc::{ self, Sender, Receiver };
+use intcode::{ Computer, Value };
+use intcode::scheduler::{ Channel, ChannelIO, Scheduler, SchedulerError };
+use intcode::snapshot::Snapshot;
 
-#[path = "computer/mod.rs"]
-mod computer;
-use computer::Computer as Computer;
+use crate::topology::{ Node, Topology };
 
-struct Amplifier {
-    phase: i32,
-    tx: Option::<Sender::<i32>>,
-    rx: Option::<Receiver::<i32>>,
+// Runs the amplifiers in the puzzle's feedback loop, returning all signals sent to the outside world
+pub fn run_phase_sequence(program: Vec<Value>, phase_sequence: Vec<Value>) -> Vec<Value> {
+    let topology = Topology::ring(phase_sequence.len());
+
+    run_topology(program, &topology, &phase_sequence)
+        .unwrap_or_else(|error| panic!("Amplifiers failed: {}", error))
 }
 
-impl Amplifier {
-    fn new(phase: i32) -> Amplifier {
-        Amplifier { phase, tx: None, rx: None }
-    }
+pub fn run_topology(
+    program: Vec<Value>,
+    topology: &Topology,
+    phase_sequence: &[Value]
+) -> Result<Vec<Value>, SchedulerError> {
+    Amplifiers::new(program, topology).run(phase_sequence)
+}
 
-    fn set_tx(&mut self, tx: Sender::<i32>) {
-        self.tx = Some(tx);
-    }
+// Amplifiers wired according to a topology. The same machines are reused for every phase sequence
+// they are run with, being reset to the initial state of the program in between.
+pub struct Amplifiers<'t> {
+    topology: &'t Topology,
+    scheduler: Scheduler<'static>,
+    initial: Snapshot,
+    // Each amplifier reads from its own channel
+    channels: Vec<Channel>,
+    // Channel from the amplifiers to the outside world
+    output: Channel,
+}
 
-    fn set_rx(&mut self, rx: Receiver::<i32>) {
-        self.rx = Some(rx);
+impl<'t> Amplifiers<'t> {
+    pub fn new(program: Vec<Value>, topology: &'t Topology) -> Amplifiers<'t> {
+        let channels = (0..topology.amplifiers())
+            .map(|_| Channel::new())
+            .collect::<Vec<Channel>>();
+        let output = Channel::new();
+
+        let mut amplifiers = Amplifiers {
+            topology,
+            scheduler: Scheduler::new(),
+            initial: Computer::new(program.clone(), None).snapshot(),
+            channels,
+            output,
+        };
+
+        for i in 0..amplifiers.channels.len() {
+            let outputs = topology.targets(Node::Amplifier(i))
+                .into_iter()
+                .map(|node| amplifiers.channel(node))
+                .collect();
+
+            amplifiers.scheduler.add(
+                Computer::new(program.clone(), None),
+                Box::new(ChannelIO::new(amplifiers.channels[i].clone(), outputs))
+            );
+        }
+
+        amplifiers
     }
 
-    fn run(&mut self, program: Vec<i32>, out_tx: Option<Sender<i32>>) -> thread::JoinHandle<()> {
-        let phase = self.phase;
-        let tx = self.tx.take().unwrap();
-        let rx = self.rx.take().unwrap();
-
-        thread::spawn(move || {
-            let mut phase_queried = false;
-
-            let input = || {
-                if !phase_queried {
-                    phase_queried = true;
-                    phase
-                } else {
-                    rx.recv().unwrap()
-                }
-            };
-
-            let output = |out_signal: i32| {
-                // This will fail for the last output of the last amplifier, since the first one
-                // will have closed the channel
-                let _ = tx.send(out_signal);
-
-                if let Some(out) = out_tx.as_ref() {
-                    out.send(out_signal).unwrap();
-                }
-            };
-
-            let mut computer = Computer::new(program, input, output);
-            computer.run();
-        })
+    // Amplifiers that go around in circles for longer than this (across all of them) make run fail
+    pub fn set_step_budget(&mut self, step_budget: Option<usize>) {
+        self.scheduler.set_step_budget(step_budget);
     }
-}
 
-pub fn run_phase_sequence(program: Vec<i32>, phase_sequence: Vec<i32>) -> i32 {
-    let mut amplifiers = setup_amplifiers(phase_sequence);
-
-    // Clone the tx for the channel the first amplifier reads from
-    let tx_start = amplifiers[amplifiers.len() - 1].tx.clone().unwrap();
-
-    let mut threads = Vec::<thread::JoinHandle<()>>::new();
-
-    let total_amplifiers = amplifiers.len();
-
-    // The first n-1 amplifiers are started regularly
-    for mut amplifier in amplifiers.drain(0..total_amplifiers-1) {
-        threads.push(amplifier.run(program.clone(), None));
+    fn channel(&self, node: Node) -> Channel {
+        match node {
+            Node::Amplifier(index) => self.channels[index].clone(),
+            Node::Output => self.output.clone(),
+            Node::Input => unreachable!("Signals cannot be sent to the input"),
+        }
     }
 
-    // Create a new channel from the last amplifier to the outside world
-    let (tx, rx) = mpsc::channel();
-
-    // The last amplifier is fed the tx to the channel we receive from
-    assert_eq!(amplifiers.len(), 1);
-    threads.push(amplifiers[0].run(program.clone(), Some(tx)));
-
-    // Kickstart process by sending initial signal to the first amplifier
-    tx_start.send(0).unwrap();
-
-    // Store received values
-    let mut values = Vec::<i32>::new();
-    for received in rx {
-        values.push(received);
-    }
+    // Each amplifier gets the phase at its index. The initial signal is 0. If the amplifiers
+    // deadlock or run out of steps, the error describes the state of each of them.
+    pub fn run(&mut self, phase_sequence: &[Value]) -> Result<Vec<Value>, SchedulerError> {
+        assert_eq!(
+            self.channels.len(), phase_sequence.len(),
+            "Topology and phase sequence sizes differ"
+        );
 
-    // All threads should've finished by now (since the last one closed the channel), so this
-    // shouldn't be necessary
-    for thread in threads {
-        thread.join().unwrap();
-    }
+        self.output.clear();
 
-    // Return the last received value
-    *values.iter().last().unwrap()
-}
+        for (i, phase) in phase_sequence.iter().enumerate() {
+            self.scheduler.restore(i, &self.initial);
 
-fn setup_amplifiers(phase_sequence: Vec<i32>) -> Vec<Amplifier> {
-    let mut amplifiers = phase_sequence
-        .iter()
-        .map(|phase| Amplifier::new(*phase))
-        .collect::<Vec<Amplifier>>();
+            // Leftovers from a previous run are dropped, and the phase is the first input
+            self.channels[i].clear();
+            self.channels[i].send(*phase);
+        }
 
-    let total_amplifiers = amplifiers.len();
+        // Kickstart process by sending the initial signal
+        for target in self.topology.targets(Node::Input) {
+            self.channel(target).send(0);
+        }
 
-    for i in 0..total_amplifiers {
-        let (tx, rx) = mpsc::channel();
+        self.scheduler.run()?;
 
-        // Connect each amplifier's tx to the next one's rx
-        amplifiers[i].set_tx(tx);
-        amplifiers[(i + 1) % total_amplifiers].set_rx(rx);
+        Ok(self.output.values())
     }
-
-    amplifiers
 }
 
 #[cfg(test)]
 mod sequencer {
     use super::*;
+    use intcode::scheduler::{ MachineState, Status };
+
+    fn last_signal(program: Vec<Value>, phase_sequence: Vec<Value>) -> Value {
+        *run_phase_sequence(program, phase_sequence).last().unwrap()
+    }
 
     #[test]
     fn test_basic_sequences() {
@@ -127,7 +119,7 @@ mod sequencer {
             run_phase_sequence(
                 vec![3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0],
                 vec![4,3,2,1,0]
-            ), 43210
+            ), vec![43210]
         );
 
         assert_eq!(
@@ -137,7 +129,7 @@ mod sequencer {
                     101,5,23,23,1,24,23,23,4,23,99,0,0
                 ],
                 vec![0,1,2,3,4]
-            ), 54321
+            ), vec![54321]
         );
 
         assert_eq!(
@@ -147,14 +139,14 @@ mod sequencer {
                     1002,33,7,33,1,33,31,31,1,32,31,31,4,31,99,0,0,0
                 ],
                 vec![1,0,4,3,2]
-            ), 65210
+            ), vec![65210]
         );
     }
 
     #[test]
     fn test_looped_sequences() {
         assert_eq!(
-            run_phase_sequence(
+            last_signal(
                 vec![
                     3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,
                     27,4,27,1001,28,-1,28,1005,28,6,99,0,0,5
@@ -164,7 +156,7 @@ mod sequencer {
         );
 
         assert_eq!(
-            run_phase_sequence(
+            last_signal(
                 vec![
                     3,52,1001,52,-5,52,3,53,1,52,56,54,1007,54,5,55,1005,55,26,1001,54,
                     -5,54,1105,1,12,1,53,54,53,1008,54,0,55,1001,55,1,55,2,53,55,53,4,
@@ -174,4 +166,95 @@ mod sequencer {
             ), 18216
         );
     }
+
+    // Adds the phase to the sum of as many signals as the phase says
+    fn summer() -> Vec<Value> {
+        intcode::assembler::assemble("
+                  in   [count]
+                  add  [count], #0, [total]
+            loop: jf   [count], #done
+                  in   [signal]
+                  add  [total], [signal], [total]
+                  add  [count], #-1, [count]
+                  jt   #1, #loop
+            done: out  [total]
+                  hlt
+            count:  data 0
+            total:  data 0
+            signal: data 0
+        ").unwrap()
+    }
+
+    #[test]
+    fn test_reused_amplifiers() {
+        let program = vec![3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0];
+        let topology = Topology::ring(5);
+        let mut amplifiers = Amplifiers::new(program, &topology);
+
+        assert_eq!(amplifiers.run(&[4,3,2,1,0]), Ok(vec![43210]));
+        assert_eq!(amplifiers.run(&[0,1,2,3,4]), Ok(vec![1234]));
+        assert_eq!(amplifiers.run(&[4,3,2,1,0]), Ok(vec![43210]));
+    }
+
+    #[test]
+    fn test_diamond_topology() {
+        let topology = "
+            in -> A
+            A -> B, C
+            B -> D, out
+            C -> D
+            D -> out
+        ".parse().unwrap();
+
+        assert_eq!(run_topology(summer(), &topology, &[1, 1, 1, 2]), Ok(vec![2, 6]));
+    }
+
+    #[test]
+    fn test_disconnected_topology() {
+        let topology = "in -> A\nA -> out\nB -> out".parse().unwrap();
+
+        assert_eq!(
+            run_topology(summer(), &topology, &[1, 1]),
+            Err(SchedulerError::Deadlock {
+                machines: vec![
+                    MachineState { machine: 0, program_counter: 24, status: Status::Halted },
+                    MachineState { machine: 1, program_counter: 9, status: Status::Blocked },
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn test_upstream_halted() {
+        // B needs two signals, but A halts after sending one
+        let topology = "in -> A\nA -> B\nB -> out".parse().unwrap();
+
+        assert_eq!(
+            run_topology(summer(), &topology, &[1, 2]),
+            Err(SchedulerError::Deadlock {
+                machines: vec![
+                    MachineState { machine: 0, program_counter: 24, status: Status::Halted },
+                    MachineState { machine: 1, program_counter: 9, status: Status::Blocked },
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn test_livelock() {
+        // Adds the phase to each signal forever, never halting
+        let looper = vec![3,13,3,14,1,13,14,14,4,14,1105,1,4,0,0];
+        let topology = Topology::ring(2);
+
+        let mut amplifiers = Amplifiers::new(looper, &topology);
+        amplifiers.set_step_budget(Some(10000));
+
+        match amplifiers.run(&[1, 1]) {
+            Err(SchedulerError::BudgetExceeded { budget: 10000, machines }) => {
+                assert_eq!(machines.len(), 2);
+                assert!(machines.iter().all(|machine| machine.status != Status::Halted));
+            }
+            result => panic!("Unexpected result {:?}", result),
+        }
+    }
 }
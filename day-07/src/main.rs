@@ -1,9 +1,16 @@
+use std::env;
 use std::fs;
+use std::thread;
 
-extern crate itertools;
-use itertools::Itertools;
+use intcode::Value;
 
 mod amplifiers;
+mod search;
+mod topology;
+use topology::Topology;
+
+// Maximum number of instructions executed by the amplifiers for a single phase sequence
+const STEP_BUDGET: usize = 10_000_000;
 
 fn main() {
     let filename = "input";
@@ -11,17 +18,48 @@ fn main() {
         |_| panic!("Failed to read from file '{}'", filename)
     );
 
-    process(contents);
+    // The puzzle's feedback loop is used unless a topology spec is given
+    let topology = env::args().nth(1).map(|filename| {
+        fs::read_to_string(&filename)
+            .unwrap_or_else(|_| panic!("Failed to read from file '{}'", filename))
+            .parse::<Topology>()
+            .unwrap_or_else(|error| panic!("Invalid topology: {}", error))
+    });
+
+    process(contents, topology.as_ref());
 }
 
-fn process(input: String) {
-    let program: Vec<i32> = input.split(",")
-        .map(|x| x.trim().parse::<i32>().unwrap())
-        .collect();
+fn process(input: String, topology: Option<&Topology>) {
+    let program = intcode::parse_program(&input)
+        .unwrap_or_else(|error| panic!("Failed to parse program: {}", error));
+
+    let ring = Topology::ring(5);
+    let amplifiers = topology.unwrap_or(&ring).amplifiers();
+    let phases = (5..5 + amplifiers as Value).collect::<Vec<Value>>();
+
+    let workers = thread::available_parallelism().map_or(1, |workers| workers.get());
+    let ranking = search::search(&program, topology.unwrap_or(&ring), &phases, workers, 5, Some(STEP_BUDGET))
+        .unwrap_or_else(|error| panic!("Amplifiers failed: {}", error));
+
+    let best = match ranking.first() {
+        Some(best) => best,
+        None => {
+            println!("No phase sequence produced a signal");
+            return;
+        }
+    };
+    println!("Highest signal: {}", best.signal);
 
-    let highest = (5..10).permutations(5)
-        .map(|phase_sequence| amplifiers::run_phase_sequence(program.clone(), phase_sequence))
-        .max();
+    // Show every signal sent to the outside world by the best sequence
+    let signals = match topology {
+        Some(topology) => amplifiers::run_topology(program.clone(), topology, &best.phase_sequence)
+            .unwrap_or_else(|error| panic!("Amplifiers failed: {}", error)),
+        None => amplifiers::run_phase_sequence(program.clone(), best.phase_sequence.clone()),
+    };
+    println!("Signals for {:?}: {:?}", best.phase_sequence, signals);
 
-    println!("Highest signal: {}", highest.unwrap());
+    println!("Top phase sequences:");
+    for (position, candidate) in ranking.iter().enumerate() {
+        println!("{:>3}. {:?} -> {}", position + 1, candidate.phase_sequence, candidate.signal);
+    }
 }
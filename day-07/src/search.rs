@@ -0,0 +1,155 @@
+use std::panic;
+use std::sync::atomic::{ AtomicBool, Ordering };
+use std::sync::Mutex;
+use std::thread;
+
+use itertools::Itertools;
+
+use intcode::Value;
+use intcode::scheduler::SchedulerError;
+
+use crate::amplifiers::Amplifiers;
+use crate::topology::Topology;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    pub phase_sequence: Vec<Value>,
+    pub signal: Value,
+}
+
+// Highest signals first. Ties are broken by phase sequence so that results do not depend on the
+// order in which workers finish.
+fn rank(candidates: &mut Vec<Candidate>, top: usize) {
+    candidates.sort_by(|a, b| {
+        b.signal.cmp(&a.signal).then_with(|| a.phase_sequence.cmp(&b.phase_sequence))
+    });
+    candidates.truncate(top);
+}
+
+// Tries every arrangement of distinct phases from the alphabet, returning the best `top` of them.
+// Phase sequences are handed out to a fixed number of workers, each of which runs all of them on
+// the same set of amplifiers. If the amplifiers fail for any sequence the search stops, and the
+// first error found is returned.
+pub fn search(
+    program: &[Value],
+    topology: &Topology,
+    phases: &[Value],
+    workers: usize,
+    top: usize,
+    step_budget: Option<usize>
+) -> Result<Vec<Candidate>, SchedulerError> {
+    // Permutations start over once exhausted, so they must be fused for workers that ask again
+    let sequences = Mutex::new(phases.iter().copied().permutations(topology.amplifiers()).fuse());
+    // Set by the first worker to fail, so that the rest stop early
+    let failed = AtomicBool::new(false);
+
+    let mut candidates = thread::scope(|scope| {
+        let handles = (0..workers.max(1))
+            .map(|_| scope.spawn(|| {
+                let mut amplifiers = Amplifiers::new(program.to_vec(), topology);
+                amplifiers.set_step_budget(step_budget);
+                let mut best = Vec::new();
+
+                loop {
+                    if failed.load(Ordering::Relaxed) {
+                        break Ok(best);
+                    }
+
+                    // The lock is released before running the amplifiers
+                    let phase_sequence = match sequences.lock().unwrap().next() {
+                        Some(phase_sequence) => phase_sequence,
+                        None => break Ok(best),
+                    };
+
+                    let signals = match amplifiers.run(&phase_sequence) {
+                        Ok(signals) => signals,
+                        Err(error) => {
+                            failed.store(true, Ordering::Relaxed);
+                            break Err(error);
+                        }
+                    };
+
+                    if let Some(&signal) = signals.last() {
+                        best.push(Candidate { phase_sequence, signal });
+
+                        // Keep memory bounded even when searching huge spaces
+                        if best.len() > 2 * top {
+                            rank(&mut best, top);
+                        }
+                    }
+                }
+            }))
+            .collect::<Vec<_>>();
+
+        // Workers only panic on bugs, which are passed on as they are
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap_or_else(|payload| panic::resume_unwind(payload)))
+            .collect::<Result<Vec<_>, _>>()
+    })?
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>();
+
+    rank(&mut candidates, top);
+    Ok(candidates)
+}
+
+#[cfg(test)]
+mod searching {
+    use super::*;
+
+    #[test]
+    fn test_ranking() {
+        let program = vec![
+            3,31,3,32,1002,32,10,32,1001,31,-2,31,1007,31,0,33,
+            1002,33,7,33,1,33,31,31,1,32,31,31,4,31,99,0,0,0
+        ];
+
+        let ranking = search(&program, &Topology::ring(5), &[0, 1, 2, 3, 4], 4, 3, None).unwrap();
+
+        assert_eq!(ranking.len(), 3);
+        assert_eq!(
+            ranking[0],
+            Candidate { phase_sequence: vec![1, 0, 4, 3, 2], signal: 65210 }
+        );
+        assert!(ranking.windows(2).all(|pair| pair[0].signal >= pair[1].signal));
+    }
+
+    #[test]
+    fn test_workers_agree() {
+        let program = vec![
+            3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,
+            27,4,27,1001,28,-1,28,1005,28,6,99,0,0,5
+        ];
+        let topology = Topology::ring(5);
+
+        let single = search(&program, &topology, &[5, 6, 7, 8, 9], 1, 10, None).unwrap();
+        let many = search(&program, &topology, &[5, 6, 7, 8, 9], 8, 10, Some(100000)).unwrap();
+
+        assert_eq!(single, many);
+        assert_eq!(single[0].phase_sequence, vec![9, 8, 7, 6, 5]);
+        assert_eq!(single[0].signal, 139629729);
+    }
+
+    #[test]
+    fn test_larger_alphabet() {
+        // Chains shorter than the alphabet only use some of the phases
+        let program = vec![3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0];
+
+        let ranking = search(&program, &Topology::ring(3), &[0, 1, 2, 3, 4, 5], 2, 1, None).unwrap();
+
+        assert_eq!(ranking, vec![Candidate { phase_sequence: vec![5, 4, 3], signal: 543 }]);
+    }
+
+    #[test]
+    fn test_failing_amplifiers() {
+        // Amplifiers given any phase but 0 jump to an unknown opcode
+        let program = vec![3,9,1005,9,7,99,0,98,0,0];
+
+        match search(&program, &Topology::ring(2), &[0, 1, 2], 4, 1, None) {
+            Err(SchedulerError::Machine { .. }) => (),
+            result => panic!("Expected a machine error, got {:?}", result),
+        }
+    }
+}
@@ -0,0 +1,202 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+// Amplifiers are named with letters, starting at A. The outside world is both the source of the
+// initial signal (in) and the destination of the final ones (out).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Node {
+    Input,
+    Amplifier(usize),
+    Output,
+}
+
+impl FromStr for Node {
+    type Err = ();
+
+    fn from_str(name: &str) -> Result<Node, ()> {
+        let mut chars = name.chars();
+
+        match (name, chars.next(), chars.next()) {
+            ("in", _, _) => Ok(Node::Input),
+            ("out", _, _) => Ok(Node::Output),
+            (_, Some(letter @ 'A'..='Z'), None) => Ok(Node::Amplifier((letter as u8 - b'A') as usize)),
+            _ => Err(()),
+        }
+    }
+}
+
+// Directed graph of amplifiers. An amplifier can feed any number of nodes (fan-out), and signals
+// from all amplifiers feeding the same one are merged in the order they are sent (fan-in).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Topology {
+    edges: Vec<(Node, Node)>,
+}
+
+impl Topology {
+    // Each amplifier feeds the next one, and the last one feeds both the first one and the
+    // outside world. There must be at least one amplifier.
+    pub fn ring(amplifiers: usize) -> Topology {
+        assert!(amplifiers > 0, "A ring needs at least one amplifier");
+
+        let mut edges = vec![(Node::Input, Node::Amplifier(0))];
+
+        for i in 0..amplifiers {
+            edges.push((Node::Amplifier(i), Node::Amplifier((i + 1) % amplifiers)));
+        }
+
+        edges.push((Node::Amplifier(amplifiers - 1), Node::Output));
+
+        Topology { edges }
+    }
+
+    // Number of amplifiers, including those not connected to anything
+    pub fn amplifiers(&self) -> usize {
+        self.edges
+            .iter()
+            .flat_map(|(from, to)| vec![from, to])
+            .filter_map(|node| match node {
+                Node::Amplifier(index) => Some(index + 1),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    // Nodes fed by a node, in the order their edges were listed
+    pub fn targets(&self, node: Node) -> Vec<Node> {
+        self.edges
+            .iter()
+            .filter(|(from, _)| *from == node)
+            .map(|(_, to)| *to)
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TopologyErrorKind {
+    MissingArrow,
+    UnknownNode(String),
+    // Signals can only come from the outside world, and only go back to it
+    InputAsDestination,
+    OutputAsSource,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TopologyError {
+    pub line: usize,
+    pub kind: TopologyErrorKind,
+}
+
+impl fmt::Display for TopologyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use TopologyErrorKind::*;
+
+        write!(f, "line {}: ", self.line)?;
+
+        match &self.kind {
+            MissingArrow => write!(f, "expected 'source -> destinations'"),
+            UnknownNode(name) => write!(f, "unknown node '{}'", name),
+            InputAsDestination => write!(f, "'in' cannot be a destination"),
+            OutputAsSource => write!(f, "'out' cannot be a source"),
+        }
+    }
+}
+
+impl Error for TopologyError {}
+
+// One connection per line, from a source to a comma-separated list of destinations:
+//
+//   # Comments start with a hash
+//   in -> A
+//   A -> B, C
+//   B -> out
+impl FromStr for Topology {
+    type Err = TopologyError;
+
+    fn from_str(spec: &str) -> Result<Topology, TopologyError> {
+        let mut edges = Vec::new();
+
+        for (index, line) in spec.lines().enumerate() {
+            let error = |kind| TopologyError { line: index + 1, kind };
+            let node = |name: &str| {
+                name.parse::<Node>()
+                    .map_err(|_| error(TopologyErrorKind::UnknownNode(name.to_string())))
+            };
+
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let mut sides = line.split("->");
+            let (from, to) = match (sides.next(), sides.next(), sides.next()) {
+                (Some(from), Some(to), None) => (from.trim(), to),
+                _ => return Err(error(TopologyErrorKind::MissingArrow)),
+            };
+
+            let from = node(from)?;
+            if from == Node::Output {
+                return Err(error(TopologyErrorKind::OutputAsSource));
+            }
+
+            for to in to.split(',') {
+                let to = node(to.trim())?;
+                if to == Node::Input {
+                    return Err(error(TopologyErrorKind::InputAsDestination));
+                }
+
+                edges.push((from, to));
+            }
+        }
+
+        Ok(Topology { edges })
+    }
+}
+
+#[cfg(test)]
+mod topologies {
+    use super::*;
+
+    #[test]
+    fn parse_ring() {
+        let spec = "
+            # The puzzle's feedback loop
+            in -> A
+            A -> B
+            B -> C
+            C -> D
+            D -> E
+            E -> A, out
+        ";
+
+        assert_eq!(spec.parse(), Ok(Topology::ring(5)));
+        assert_eq!(Topology::ring(5).amplifiers(), 5);
+        assert_eq!(
+            Topology::ring(5).targets(Node::Amplifier(4)),
+            vec![Node::Amplifier(0), Node::Output]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "A ring needs at least one amplifier")]
+    fn empty_ring() {
+        Topology::ring(0);
+    }
+
+    fn assert_error(spec: &str, line: usize, kind: TopologyErrorKind) {
+        assert_eq!(spec.parse::<Topology>(), Err(TopologyError { line, kind }));
+    }
+
+    #[test]
+    fn errors() {
+        use TopologyErrorKind::*;
+
+        assert_error("in -> A\nA B", 2, MissingArrow);
+        assert_error("A -> B -> C", 1, MissingArrow);
+        assert_error("in -> a", 1, UnknownNode("a".to_string()));
+        assert_error("A -> B,", 1, UnknownNode("".to_string()));
+        assert_error("A -> in", 1, InputAsDestination);
+        assert_error("\nout -> A", 2, OutputAsSource);
+    }
+}
@@ -1,16 +1,25 @@
 use std::io::{ self, Write };
 use core::convert::{ TryInto, TryFrom };
+use std::env;
 use std::fs;
 
+use grid::{ ocr, Grid, Image, Orientation, Position, Rgb };
+
 fn main() {
     let filename = "input";
     let contents = fs::read_to_string(filename).unwrap_or_else(
         |_| panic!("Failed to read from file '{}'", filename)
     );
 
-    process(&contents, 25, 6);
+    // --image <file> also saves the decoded picture as a ppm, png or svg file
+    let image = env::args().skip_while(|arg| arg != "--image").nth(1);
+
+    process(&contents, 25, 6, image);
 }
 
+// Pixels per pixel of the picture in saved images
+const CELL_SIZE: usize = 10;
+
 #[derive(PartialEq)]
 #[derive(Debug)]
 enum Color {
@@ -59,7 +68,7 @@ impl Layer {
     }
 }
 
-fn process(input: &str, width: usize, height: usize) {
+fn process(input: &str, width: usize, height: usize, image: Option<String>) {
     let layers = Layer::from_input(input, width, height);
 
     let fewest_zeroes_layer = layers
@@ -97,4 +106,29 @@ fn process(input: &str, width: usize, height: usize) {
         print!("\n");
         io::stdout().flush().unwrap();
     }
+
+    let pixels: Grid<&Color> = picture
+        .iter()
+        .enumerate()
+        .map(|(pixel_num, color)| (Position::new((pixel_num % width) as i64, (pixel_num / width) as i64), color.unwrap()))
+        .collect();
+
+    let message = ocr::read(&pixels, Orientation::YDown, |color| **color == Color::White)
+        .unwrap_or_else(|error| panic!("Failed to read message: {}", error));
+    println!("Message: {}", message);
+
+    // Test correctness
+    assert_eq!(message, "CJZHR");
+
+    if let Some(path) = image {
+        let image = Image::from_fn(width, height, |x, y| match picture[y * width + x].unwrap() {
+            Color::Black => Rgb::BLACK,
+            Color::White => Rgb::WHITE,
+            _ => unreachable!("Pictures cannot have transparent colors"),
+        });
+
+        image
+            .save(&path, CELL_SIZE)
+            .unwrap_or_else(|error| panic!("Failed to save image to '{}': {}", path, error));
+    }
 }
@@ -1,7 +1,6 @@
 use std::fs;
 
-mod computer;
-use computer::*;
+use intcode::*;
 
 fn main() {
     let filename = "input";
@@ -13,10 +12,11 @@ fn main() {
 }
 
 fn process(input: String) {
-    let program: Vec<_> = input.split(",")
-        .map(|x| x.trim().parse::<i64>().unwrap())
-        .collect();
+    let program = intcode::parse_program(&input)
+        .unwrap_or_else(|error| panic!("Failed to parse program: {}", error));
 
-    let mut computer = Computer::new_with_terminal(program.clone());
-    computer.run();
+    let mut computer = Computer::new(program, None);
+    computer
+        .run()
+        .unwrap_or_else(|error| panic!("Program failed: {}", error));
 }
@@ -1,8 +1,9 @@
+use std::env;
 use std::fs;
 use std::io::{self, Write};
 
-mod computer;
-use computer::*;
+use grid::{ocr, Image, Orientation, Renderer, Rgb};
+use intcode::*;
 
 mod robot;
 use robot::*;
@@ -12,40 +13,56 @@ fn main() {
     let contents = fs::read_to_string(filename)
         .unwrap_or_else(|_| panic!("Failed to read from file '{}'", filename));
 
-    let program: Vec<_> = contents
-        .split(",")
-        .map(|x| x.trim().parse::<i64>().unwrap())
-        .collect();
+    let program = intcode::parse_program(&contents)
+        .unwrap_or_else(|error| panic!("Failed to parse program: {}", error));
 
-    process(program);
+    // --image <file> also saves the painted hull as a ppm, png or svg file
+    let image = env::args().skip_while(|arg| arg != "--image").nth(1);
+
+    process(program, image);
 }
 
-fn process(program: Vec<i64>) {
+// Pixels per panel in saved images
+const CELL_SIZE: usize = 10;
+
+fn process(program: Vec<i64>, image: Option<String>) {
     let mut robot = Robot::new();
 
-    let mut computer = Computer::new(program, Some(&mut robot));
-    computer.run();
+    let mut computer = Computer::new(program, None);
+    robot
+        .run(&mut computer)
+        .unwrap_or_else(|error| panic!("Program failed: {}", error));
+
+    println!("Painted {:?} cells", robot.grid.len());
 
-    println!("Painted {:?} cells", robot.grid.colors.len());
+    let bounds = robot.grid.bounds().unwrap();
+    println!("X: {:?}, {:?}", bounds.min.x, bounds.max.x);
+    println!("Y: {:?}, {:?}", bounds.min.y, bounds.max.y);
 
-    let max_x = robot.grid.colors.keys().map(|pos| pos.x).max().unwrap();
-    let min_x = robot.grid.colors.keys().map(|pos| pos.x).min().unwrap();
+    // The robot starts facing up
+    let mut renderer = Renderer::new(|color: Option<&Color>| match color.copied().unwrap_or(Color::White) {
+        Color::White => '#',
+        Color::Black => ' ',
+    });
+    renderer.set_orientation(Orientation::YUp);
 
-    let max_y = robot.grid.colors.keys().map(|pos| pos.y).max().unwrap();
-    let min_y = robot.grid.colors.keys().map(|pos| pos.y).min().unwrap();
+    print!("{}", renderer.render(&robot.grid));
+    io::stdout().flush().unwrap();
 
-    println!("X: {:?}, {:?}", min_x, max_x);
-    println!("Y: {:?}, {:?}", min_y, max_y);
+    // Only cells painted white spell the registration, the rest of the hull is just left white
+    let registration = ocr::read(&robot.grid, Orientation::YUp, |&color| color == Color::White)
+        .unwrap_or_else(|error| panic!("Failed to read registration: {}", error));
+    println!("Registration: {}", registration);
 
-    for y in (min_y..=max_y).rev() {
-        for x in min_x..=max_x {
-            match robot.grid.get(&Position::new(x, y)) {
-                Color::White => print!("#"),
-                Color::Black => print!(" "),
-            }
-        }
+    // Test correctness
+    assert_eq!(registration, "GARPKZUL");
 
-        print!("\n");
-        io::stdout().flush().unwrap();
+    if let Some(path) = image {
+        Image::from_grid(&robot.grid, Orientation::YUp, |color: Option<&Color>| match color.copied().unwrap_or(Color::White) {
+            Color::White => Rgb::WHITE,
+            Color::Black => Rgb::BLACK,
+        })
+        .save(&path, CELL_SIZE)
+        .unwrap_or_else(|error| panic!("Failed to save image to '{}': {}", path, error));
     }
 }
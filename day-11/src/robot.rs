@@ -1,21 +1,9 @@
 use core::convert::{TryFrom, TryInto};
-use std::collections::HashMap;
 
-use super::computer::*;
+use grid::{Direction, Grid, Heading, Position, Turn};
+use intcode::*;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct Position {
-    pub x: i64,
-    pub y: i64,
-}
-
-impl Position {
-    pub fn new(x: i64, y: i64) -> Position {
-        Position { x, y }
-    }
-}
-
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Color {
     Black = 0,
     White,
@@ -33,121 +21,54 @@ impl TryFrom<hardware::Value> for Color {
     }
 }
 
-pub struct Grid {
-    pub colors: HashMap<Position, Color>,
-}
-
-impl Grid {
-    fn new() -> Grid {
-        Grid {
-            colors: HashMap::new(),
-        }
-    }
-
-    pub fn get(&self, position: &Position) -> Color {
-        *self.colors.get(position).unwrap_or(&Color::White)
-    }
-
-    fn paint(&mut self, position: &Position, color: Color) {
-        self.colors.insert(*position, color);
-    }
-}
-
-enum State {
-    ReadyToPaint,
-    ReadyToMove,
-}
-
-struct Heading {
-    x: i64,
-    y: i64,
-}
-
-enum RotateDirection {
-    Clockwise,
-    CounterClockwise,
-}
-
-impl TryFrom<hardware::Value> for RotateDirection {
-    type Error = hardware::Value;
-
-    fn try_from(x: hardware::Value) -> Result<Self, Self::Error> {
-        match x {
-            0 => Ok(RotateDirection::CounterClockwise),
-            1 => Ok(RotateDirection::Clockwise),
-            _ => Err(x),
-        }
-    }
-}
-
-impl Heading {
-    fn rotate(&mut self, direction: RotateDirection) {
-        let (cos, sin) = match direction {
-            RotateDirection::Clockwise => {
-                (0, -1) // cos(-pi/2), sin(-pi/2)
-            }
-            RotateDirection::CounterClockwise => {
-                (0, 1) // cos(pi/2), sin(pi/2)
-            }
-        };
-
-        let new_x = self.x * cos - self.y * sin;
-        let new_y = self.x * sin + self.y * cos;
-
-        self.x = new_x;
-        self.y = new_y;
+fn turn(x: hardware::Value) -> Result<Turn, hardware::Value> {
+    match x {
+        0 => Ok(Turn::Left),
+        1 => Ok(Turn::Right),
+        _ => Err(x),
     }
 }
 
 pub struct Robot {
-    position: Position,
     heading: Heading,
-    pub grid: Grid,
-    state: State,
+    // Only painted cells are stored, the rest are white
+    pub grid: Grid<Color>,
 }
 
 impl Robot {
     pub fn new() -> Robot {
         Robot {
-            position: Position::new(0, 0),
+            heading: Heading::new(Position::origin(), Direction::Up),
             grid: Grid::new(),
-            state: State::ReadyToPaint,
-            heading: Heading { x: 0, y: 1 },
         }
     }
 
-    fn scan(&self) -> Color {
-        self.grid.get(&self.position)
-    }
-
-    fn rotate(&mut self, direction: RotateDirection) {
-        self.heading.rotate(direction);
+    pub fn color(&self, position: Position) -> Color {
+        self.grid.get_or(position, Color::White)
     }
 
-    fn advance(&mut self, speed: i64) {
-        self.position.x += self.heading.x * speed;
-        self.position.y += self.heading.y * speed;
-    }
-}
-
-impl hardware::IO for Robot {
-    fn input(&mut self) -> hardware::Value {
-        self.scan() as hardware::Value
+    fn scan(&self) -> Color {
+        self.color(self.heading.position)
     }
 
-    fn output(&mut self, value: hardware::Value) {
-        match &self.state {
-            State::ReadyToPaint => {
-                self.grid.paint(&self.position, value.try_into().unwrap());
-
-                self.state = State::ReadyToMove;
-            }
-            State::ReadyToMove => {
-                let direction = value.try_into().unwrap();
-                self.rotate(direction);
-                self.advance(1);
-
-                self.state = State::ReadyToPaint;
+    // Feeds the camera to the computer and follows its instructions until it halts. Each
+    // instruction is made of two outputs: the color to paint and the direction to turn to.
+    pub fn run(&mut self, computer: &mut Computer) -> Result<(), IntcodeError> {
+        loop {
+            match computer.run_until_event()? {
+                Event::NeedsInput => computer.provide_input(self.scan() as hardware::Value),
+                Event::Output(color) => {
+                    self.grid.insert(self.heading.position, color.try_into().unwrap());
+
+                    match computer.run_until_event()? {
+                        Event::Output(direction) => {
+                            self.heading.turn(turn(direction).unwrap());
+                            self.heading.advance(1);
+                        }
+                        event => panic!("Expected direction, got {:?}", event),
+                    }
+                }
+                Event::Halted => break Ok(()),
             }
         }
     }
@@ -0,0 +1,68 @@
+use std::io::{self, Write};
+use std::thread;
+use std::time::Duration;
+
+use grid::Renderer;
+
+use super::game::GameState;
+
+extern crate termion;
+
+// Somewhere to show the game. Frontends are only shown the game state, and cannot affect it.
+pub trait Frontend {
+    fn draw(&mut self, state: &GameState);
+}
+
+// The board followed by the score
+pub fn frame(state: &GameState) -> String {
+    format!(
+        "{}Score: {:?}\n",
+        Renderer::with_glyphs().render(state.objects()),
+        state.score()
+    )
+}
+
+// Draws every frame over the previous one, and waits a bit after each so that the game can be
+// followed
+pub struct Terminal {
+    delay: Duration,
+}
+
+impl Terminal {
+    pub fn new(delay: Duration) -> Terminal {
+        print!("{}", termion::cursor::Save);
+
+        Terminal { delay }
+    }
+}
+
+impl Frontend for Terminal {
+    fn draw(&mut self, state: &GameState) {
+        print!("{}", termion::cursor::Restore);
+        print!("{}", frame(state));
+        io::stdout().flush().unwrap();
+
+        thread::sleep(self.delay);
+    }
+}
+
+// Keeps every frame, e.g. to check or save them once the game is over
+pub struct Recorder {
+    frames: Vec<String>,
+}
+
+impl Recorder {
+    pub fn new() -> Recorder {
+        Recorder { frames: Vec::new() }
+    }
+
+    pub fn frames(&self) -> &[String] {
+        &self.frames
+    }
+}
+
+impl Frontend for Recorder {
+    fn draw(&mut self, state: &GameState) {
+        self.frames.push(frame(state));
+    }
+}
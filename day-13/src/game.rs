@@ -1,27 +1,14 @@
 use core::convert::{TryFrom, TryInto};
-use std::collections::HashMap;
-use std::io::{self, Write};
-use std::thread;
-use std::time;
 
-use super::computer::*;
+use grid::{Glyph, Grid, Image, Orientation, Palette, Position, Rgb};
+use intcode::profiler::Profiler;
+use intcode::*;
 
-extern crate termion;
+use super::frontend::Frontend;
+use super::strategy::PaddleStrategy;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct Position {
-    pub x: i64,
-    pub y: i64,
-}
-
-impl Position {
-    pub fn new(x: i64, y: i64) -> Position {
-        Position { x, y }
-    }
-}
-
-#[derive(Clone, Copy, PartialEq)]
-enum Object {
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Object {
     Empty,
     Wall,
     Block,
@@ -29,14 +16,14 @@ enum Object {
     Ball,
 }
 
-impl Object {
-    fn draw(self) {
+impl Glyph for Object {
+    fn glyph(&self) -> char {
         match self {
-            Object::Empty => print!(" "),
-            Object::Wall => print!("|"),
-            Object::Block => print!("#"),
-            Object::Paddle => print!("="),
-            Object::Ball => print!("o"),
+            Object::Empty => ' ',
+            Object::Wall => '|',
+            Object::Block => '#',
+            Object::Paddle => '=',
+            Object::Ball => 'o',
         }
     }
 }
@@ -94,86 +81,269 @@ impl Buffer {
     }
 }
 
-pub struct GameDisplay {
-    pub score: hardware::Value,
-    objects: HashMap<Position, Object>,
+// Everything the program has drawn so far. It only changes with the program's outputs, so the same
+// program and inputs always lead to the same state.
+pub struct GameState {
+    objects: Grid<Object>,
+    score: hardware::Value,
+    blocks: usize,
+    ball: Option<Position>,
+    paddle: Option<Position>,
     buffer: Buffer,
 }
 
-impl GameDisplay {
-    pub fn new() -> GameDisplay {
-        print!("{}", termion::cursor::Save);
-
-        GameDisplay {
-            buffer: Buffer::new(),
+impl GameState {
+    pub fn new() -> GameState {
+        GameState {
+            objects: Grid::new(),
             score: 0,
-            objects: HashMap::new(),
+            blocks: 0,
+            ball: None,
+            paddle: None,
+            buffer: Buffer::new(),
         }
     }
 
-    fn draw(&self) {
-        print!("{}", termion::cursor::Restore);
+    pub fn objects(&self) -> &Grid<Object> {
+        &self.objects
+    }
+
+    pub fn score(&self) -> hardware::Value {
+        self.score
+    }
+
+    // Blocks left on the board
+    pub fn blocks(&self) -> usize {
+        self.blocks
+    }
+
+    pub fn ball(&self) -> Option<Position> {
+        self.ball
+    }
 
-        let max_x = self.objects.keys().map(|pos| pos.x).max().unwrap();
-        let min_x = self.objects.keys().map(|pos| pos.x).min().unwrap();
+    pub fn paddle(&self) -> Option<Position> {
+        self.paddle
+    }
 
-        let max_y = self.objects.keys().map(|pos| pos.y).max().unwrap();
-        let min_y = self.objects.keys().map(|pos| pos.y).min().unwrap();
+    // The board as currently drawn, with empty tiles left black
+    pub fn image(&self) -> Image {
+        let mut palette = Palette::new(Rgb::BLACK);
+        palette.set(Object::Wall.glyph(), Rgb(128, 128, 128));
+        palette.set(Object::Block.glyph(), Rgb(255, 160, 0));
+        palette.set(Object::Paddle.glyph(), Rgb::WHITE);
+        palette.set(Object::Ball.glyph(), Rgb(255, 0, 0));
 
-        for y in min_y..=max_y {
-            for x in min_x..=max_x {
-                let object = self
-                    .objects
-                    .get(&Position::new(x, y))
-                    .unwrap_or(&Object::Empty);
+        Image::from_glyphs(&self.objects, Orientation::YDown, &palette)
+    }
 
-                object.draw();
+    // Objects and scores take three outputs each, so most outputs only get buffered
+    pub fn output(&mut self, value: hardware::Value) {
+        if let Some((position, data)) = self.buffer.insert(value) {
+            match data {
+                Data::Object(object) => self.place(position, object),
+                Data::Score(score) => self.score = score,
             }
+        }
+    }
 
-            println!("");
-            io::stdout().flush().unwrap();
+    fn place(&mut self, position: Position, object: Object) {
+        if self.objects.insert(position, object) == Some(Object::Block) {
+            self.blocks -= 1;
         }
 
-        println!("Score: {:?}", self.score);
+        if self.ball == Some(position) {
+            self.ball = None;
+        }
+        if self.paddle == Some(position) {
+            self.paddle = None;
+        }
+
+        match object {
+            Object::Block => self.blocks += 1,
+            Object::Ball => self.ball = Some(position),
+            Object::Paddle => self.paddle = Some(position),
+            _ => (),
+        }
     }
 }
 
-impl hardware::IO for GameDisplay {
-    fn input(&mut self) -> hardware::Value {
-        self.draw();
-        thread::sleep(time::Duration::from_millis(50));
+// Plays the game: the program's outputs go into the game state, and every time the program waits
+// for the joystick the state is shown on the frontend (if any) before the strategy moves the paddle.
+pub struct Game<'g> {
+    state: GameState,
+    strategy: &'g mut dyn PaddleStrategy,
+    frontend: Option<&'g mut dyn Frontend>,
+    inputs: usize,
+    moves: usize,
+    quit: bool,
+}
 
-        let (ball_position, _) = self
-            .objects
-            .iter()
-            .find(|(_position, object)| **object == Object::Ball)
-            .unwrap();
+impl<'g> Game<'g> {
+    pub fn new(
+        strategy: &'g mut dyn PaddleStrategy,
+        frontend: Option<&'g mut dyn Frontend>,
+    ) -> Game<'g> {
+        Game {
+            state: GameState::new(),
+            strategy,
+            frontend,
+            inputs: 0,
+            moves: 0,
+            quit: false,
+        }
+    }
 
-        let (paddle_position, _) = self
-            .objects
-            .iter()
-            .find(|(_position, object)| **object == Object::Paddle)
-            .unwrap();
+    // Runs the program until the game is over or the strategy quits
+    pub fn play(
+        &mut self,
+        program: &[hardware::Value],
+        profiler: Option<&mut Profiler>,
+    ) -> Result<(), IntcodeError> {
+        let mut computer = Computer::new(program.to_vec(), Some(self));
+        if let Some(profiler) = profiler {
+            computer.set_profiler(profiler);
+        }
 
-        if ball_position.x > paddle_position.x {
-            1
-        } else if ball_position.x < paddle_position.x {
-            -1
-        } else {
-            0
+        match computer.run() {
+            // The program is left waiting for a joystick position it will never get
+            Err(IntcodeError::InputExhausted { .. }) if self.quit => Ok(()),
+            result => result,
         }
     }
 
-    fn output(&mut self, value: hardware::Value) {
-        if let Some((position, data)) = self.buffer.insert(value) {
-            match data {
-                Data::Object(object) => {
-                    self.objects.insert(position, object);
-                }
-                Data::Score(score) => {
-                    self.score = score;
+    pub fn state(&self) -> &GameState {
+        &self.state
+    }
+
+    // Number of times the joystick was read
+    pub fn inputs(&self) -> usize {
+        self.inputs
+    }
+
+    // Number of times the paddle was moved
+    pub fn moves(&self) -> usize {
+        self.moves
+    }
+
+    // Whether the strategy quit before the game was over
+    pub fn quit(&self) -> bool {
+        self.quit
+    }
+}
+
+impl hardware::IO for Game<'_> {
+    fn input(&mut self) -> Option<hardware::Value> {
+        if let Some(frontend) = self.frontend.as_mut() {
+            frontend.draw(&self.state);
+        }
+
+        let joystick = self.strategy.joystick(&self.state);
+
+        match joystick {
+            Some(joystick) => {
+                self.inputs += 1;
+                if joystick != 0 {
+                    self.moves += 1;
                 }
             }
+            None => self.quit = true,
         }
+
+        joystick
+    }
+
+    fn output(&mut self, value: hardware::Value) {
+        self.state.output(value);
+    }
+}
+
+// Draws a block, a ball to the right of a paddle, and then sets the score to the joystick's position
+#[cfg(test)]
+pub const SHORT_GAME: &[hardware::Value] = &[
+    104, 0, 104, 0, 104, 2, 104, 3, 104, 1, 104, 4, 104, 1, 104, 2, 104, 3, 104, -1, 104, 0, 3, 27,
+    4, 27, 99, 0,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::super::frontend::Recorder;
+    use super::super::strategy::FollowBall;
+    use super::*;
+
+    struct Quit;
+
+    impl PaddleStrategy for Quit {
+        fn joystick(&mut self, _state: &GameState) -> Option<hardware::Value> {
+            None
+        }
+    }
+
+    fn draw(state: &mut GameState, x: hardware::Value, y: hardware::Value, value: hardware::Value) {
+        for value in &[x, y, value] {
+            state.output(*value);
+        }
+    }
+
+    #[test]
+    fn test_state_tracking() {
+        let mut state = GameState::new();
+        draw(&mut state, 0, 0, 2);
+        draw(&mut state, 1, 0, 2);
+        draw(&mut state, 0, 2, 3);
+        draw(&mut state, 1, 1, 4);
+        draw(&mut state, -1, 0, 12);
+
+        assert_eq!(state.blocks(), 2);
+        assert_eq!(state.ball(), Some(Position::new(1, 1)));
+        assert_eq!(state.paddle(), Some(Position::new(0, 2)));
+        assert_eq!(state.score(), 12);
+
+        // The ball breaks a block and leaves an empty tile behind
+        draw(&mut state, 1, 1, 0);
+        draw(&mut state, 1, 0, 4);
+
+        assert_eq!(state.blocks(), 1);
+        assert_eq!(state.ball(), Some(Position::new(1, 0)));
+    }
+
+    #[test]
+    fn test_recorded_game() {
+        let mut recorder = Recorder::new();
+        let mut strategy = FollowBall;
+        let mut game = Game::new(&mut strategy, Some(&mut recorder));
+        Computer::new(SHORT_GAME.to_vec(), Some(&mut game))
+            .run()
+            .unwrap();
+
+        assert_eq!(game.state().score(), 1);
+        assert_eq!(game.state().blocks(), 1);
+        assert_eq!(
+            recorder.frames(),
+            &["#   \n   o\n =  \nScore: 0\n".to_string()]
+        );
+
+        let mut game = Game::new(&mut strategy, None);
+        Computer::new(SHORT_GAME.to_vec(), Some(&mut game))
+            .run()
+            .unwrap();
+        assert_eq!(game.state().score(), 1);
+    }
+
+    #[test]
+    fn test_quit() {
+        // Reads the joystick once and halts
+        let program = [3, 0, 99];
+
+        let mut strategy = Quit;
+        let mut game = Game::new(&mut strategy, None);
+        assert_eq!(game.play(&program, None), Ok(()));
+        assert!(game.quit());
+        assert_eq!(game.inputs(), 0);
+
+        let mut strategy = FollowBall;
+        let mut game = Game::new(&mut strategy, None);
+        assert_eq!(game.play(&program, None), Ok(()));
+        assert!(!game.quit());
+        assert_eq!(game.inputs(), 1);
     }
 }
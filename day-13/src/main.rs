@@ -1,28 +1,148 @@
+use std::env;
 use std::fs;
+use std::time::Duration;
 
-mod computer;
-use computer::*;
+use intcode::profiler::Profiler;
+
+mod frontend;
+use frontend::*;
 
 mod game;
 use game::*;
 
+mod strategy;
+use strategy::*;
+
 fn main() {
     let filename = "input";
     let contents = fs::read_to_string(filename)
         .unwrap_or_else(|_| panic!("Failed to read from file '{}'", filename));
 
-    let program: Vec<_> = contents
-        .split(',')
-        .map(|x| x.trim().parse::<i64>().unwrap())
-        .collect();
+    let program = intcode::parse_program(&contents)
+        .unwrap_or_else(|error| panic!("Failed to parse program: {}", error));
+
+    // --profile reports where the program spends its steps, and --annotate adds a disassembly
+    // with the number of times each instruction ran
+    let annotate = env::args().any(|arg| arg == "--annotate");
+    let profile = annotate || env::args().any(|arg| arg == "--profile");
+
+    // --image <file> saves the final board as a ppm, png or svg file
+    let image = env::args().skip_while(|arg| arg != "--image").nth(1);
+
+    // The game is played on the terminal unless --headless is given, or --record <file> saves all
+    // frames to a file instead
+    let display = match env::args().skip_while(|arg| arg != "--record").nth(1) {
+        Some(path) => Display::Record(path),
+        None if env::args().any(|arg| arg == "--headless") => Display::Headless,
+        None => Display::Terminal,
+    };
+
+    // --evaluate plays a game with each automatic strategy and compares how they did
+    if env::args().any(|arg| arg == "--evaluate") {
+        compare(&program);
+        return;
+    }
+
+    // --strategy <name> picks who plays: follow (the default), predict or keyboard
+    let name = env::args().skip_while(|arg| arg != "--strategy").nth(1);
+    let mut strategy: Box<dyn PaddleStrategy> = match name.as_deref() {
+        None | Some("follow") => Box::new(FollowBall),
+        Some("predict") => Box::new(PredictLanding::new()),
+        Some("keyboard") => Box::new(Keyboard::new()),
+        Some(name) => panic!("Unknown strategy '{}'", name),
+    };
 
-    process(program);
+    process(program, strategy.as_mut(), profile, annotate, image, display);
 }
 
-fn process(program: Vec<i64>) {
-    let mut game_display = GameDisplay::new();
-    let mut computer = Computer::new(program, Some(&mut game_display));
-    computer.run();
+fn compare(program: &[i64]) {
+    let strategies: Vec<(&str, Box<dyn PaddleStrategy>)> = vec![
+        ("follow", Box::new(FollowBall)),
+        ("predict", Box::new(PredictLanding::new())),
+    ];
+
+    println!("{:<10} {:>8} {:>8} {:>8}  Result", "Strategy", "Score", "Inputs", "Moves");
+
+    for (name, mut strategy) in strategies {
+        let evaluation = evaluate(program, strategy.as_mut())
+            .unwrap_or_else(|error| panic!("Program failed: {}", error));
+
+        println!(
+            "{:<10} {:>8} {:>8} {:>8}  {}",
+            name,
+            evaluation.score,
+            evaluation.inputs,
+            evaluation.moves,
+            if evaluation.won { "won" } else { "lost" }
+        );
+    }
+}
+
+enum Display {
+    Terminal,
+    Record(String),
+    Headless,
+}
+
+// Time each frame is shown on the terminal
+const FRAME_DELAY: Duration = Duration::from_millis(50);
+
+// Number of addresses listed in the profile
+const HOT_SPOTS: usize = 20;
+
+// Pixels per tile in saved images
+const CELL_SIZE: usize = 10;
+
+fn process(
+    program: Vec<i64>,
+    strategy: &mut dyn PaddleStrategy,
+    profile: bool,
+    annotate: bool,
+    image: Option<String>,
+    display: Display,
+) {
+    let mut terminal;
+    let mut recorder = Recorder::new();
+
+    let frontend: Option<&mut dyn Frontend> = match display {
+        Display::Terminal => {
+            terminal = Terminal::new(FRAME_DELAY);
+            Some(&mut terminal)
+        }
+        Display::Record(_) => Some(&mut recorder),
+        Display::Headless => None,
+    };
+
+    let mut game = Game::new(strategy, frontend);
+    let mut profiler = Profiler::new();
+
+    game.play(&program, if profile { Some(&mut profiler) } else { None })
+        .unwrap_or_else(|error| panic!("Program failed: {}", error));
+
+    if game.quit() {
+        println!("Quit before the game was over");
+    }
+
+    println!("Final score: {:?}", game.state().score());
+    println!("Blocks left: {:?}", game.state().blocks());
+
+    if let Some(path) = image {
+        game.state()
+            .image()
+            .save(&path, CELL_SIZE)
+            .unwrap_or_else(|error| panic!("Failed to save image to '{}': {}", path, error));
+    }
+
+    if let Display::Record(path) = display {
+        fs::write(&path, recorder.frames().join("\n"))
+            .unwrap_or_else(|error| panic!("Failed to save frames to '{}': {}", path, error));
+    }
+
+    if profile {
+        print!("\n{}", profiler.report(HOT_SPOTS));
+    }
 
-    println!("Final score: {:?}", game_display.score);
+    if annotate {
+        print!("\n{}", profiler.annotate(&program));
+    }
 }
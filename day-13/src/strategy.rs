@@ -0,0 +1,188 @@
+use std::io;
+
+use grid::Position;
+use intcode::*;
+
+use termion::event::Key;
+use termion::input::{Keys, TermRead};
+use termion::raw::IntoRawMode;
+
+use super::game::{Game, GameState};
+
+// Decides where to move the paddle each time the program asks for the joystick's position: -1 to
+// the left, 0 to stay and 1 to the right. None quits the game.
+pub trait PaddleStrategy {
+    fn joystick(&mut self, state: &GameState) -> Option<hardware::Value>;
+}
+
+// Moves the paddle towards the ball's current position
+pub struct FollowBall;
+
+impl PaddleStrategy for FollowBall {
+    fn joystick(&mut self, state: &GameState) -> Option<hardware::Value> {
+        match (state.ball(), state.paddle()) {
+            (Some(ball), Some(paddle)) => Some((ball.x - paddle.x).signum()),
+            _ => Some(0),
+        }
+    }
+}
+
+// Works out the ball's velocity from its last two positions, and while it falls moves the paddle to
+// where it will reach the paddle's row, bouncing off the walls on the way. Blocks in the ball's path
+// are not taken into account.
+pub struct PredictLanding {
+    previous: Option<Position>,
+}
+
+impl PredictLanding {
+    pub fn new() -> PredictLanding {
+        PredictLanding { previous: None }
+    }
+}
+
+impl PaddleStrategy for PredictLanding {
+    fn joystick(&mut self, state: &GameState) -> Option<hardware::Value> {
+        let (ball, paddle) = match (state.ball(), state.paddle()) {
+            (Some(ball), Some(paddle)) => (ball, paddle),
+            _ => return Some(0),
+        };
+
+        let target = match self.previous.replace(ball) {
+            Some(previous) if ball.y > previous.y => {
+                landing(state, ball, ball.x - previous.x, paddle.y)
+            }
+            _ => ball.x,
+        };
+
+        Some((target - paddle.x).signum())
+    }
+}
+
+// Column at which a ball moving down diagonally reaches the row right above the paddle
+fn landing(state: &GameState, ball: Position, mut dx: i64, paddle_y: i64) -> i64 {
+    // Walls surround the board
+    let bounds = state.objects().bounds().unwrap();
+    let columns = bounds.min.x + 1..=bounds.max.x - 1;
+
+    let mut x = ball.x;
+    for _ in ball.y..paddle_y - 1 {
+        if !columns.contains(&(x + dx)) {
+            dx = -dx;
+        }
+
+        x += dx;
+    }
+
+    x
+}
+
+// A human at the keyboard: the left and right arrows (or 'a' and 'd') move the paddle, any other
+// key leaves it where it is, and 'q' (or Ctrl-C, or the end of the input) quits. The game waits for
+// a key on every move.
+pub struct Keyboard {
+    keys: Keys<io::Stdin>,
+}
+
+impl Keyboard {
+    pub fn new() -> Keyboard {
+        Keyboard {
+            keys: io::stdin().keys(),
+        }
+    }
+}
+
+impl PaddleStrategy for Keyboard {
+    fn joystick(&mut self, _state: &GameState) -> Option<hardware::Value> {
+        // Raw mode is only needed while reading, and would get in the way of drawing
+        let raw = io::stdout()
+            .into_raw_mode()
+            .unwrap_or_else(|error| panic!("Failed to read from the keyboard: {}", error));
+        let key = self.keys.next();
+        drop(raw);
+
+        match key {
+            Some(Ok(Key::Left)) | Some(Ok(Key::Char('a'))) => Some(-1),
+            Some(Ok(Key::Right)) | Some(Ok(Key::Char('d'))) => Some(1),
+            Some(Ok(Key::Char('q'))) | Some(Ok(Key::Ctrl('c'))) | None => None,
+            _ => Some(0),
+        }
+    }
+}
+
+// How a strategy did over a whole game
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Evaluation {
+    pub score: hardware::Value,
+    pub inputs: usize,
+    // Inputs that moved the paddle
+    pub moves: usize,
+    // The game is won when all blocks are broken, and lost when the ball is missed or the strategy
+    // quits
+    pub won: bool,
+}
+
+// Plays a whole game with a strategy, without drawing it
+pub fn evaluate(
+    program: &[hardware::Value],
+    strategy: &mut dyn PaddleStrategy,
+) -> Result<Evaluation, IntcodeError> {
+    let mut game = Game::new(strategy, None);
+    game.play(program, None)?;
+
+    Ok(Evaluation {
+        score: game.state().score(),
+        inputs: game.inputs(),
+        moves: game.moves(),
+        won: game.state().blocks() == 0,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::game::SHORT_GAME;
+    use super::*;
+
+    // Walls at x = 0 and x = 6, with the paddle at the bottom
+    fn state(ball: (i64, i64), paddle: (i64, i64)) -> GameState {
+        let mut state = GameState::new();
+        for value in &[0, 0, 1, 6, 0, 1, ball.0, ball.1, 4, paddle.0, paddle.1, 3] {
+            state.output(*value);
+        }
+
+        state
+    }
+
+    #[test]
+    fn test_follow_ball() {
+        assert_eq!(FollowBall.joystick(&state((1, 1), (3, 6))), Some(-1));
+        assert_eq!(FollowBall.joystick(&state((3, 1), (3, 6))), Some(0));
+        assert_eq!(FollowBall.joystick(&state((5, 1), (3, 6))), Some(1));
+    }
+
+    #[test]
+    fn test_predict_landing() {
+        let mut strategy = PredictLanding::new();
+
+        // The ball's direction is not known yet, so it is followed
+        assert_eq!(strategy.joystick(&state((3, 1), (3, 6))), Some(0));
+        // Moving down and right, it bounces off the right wall and lands at x = 3
+        assert_eq!(
+            landing(&state((4, 2), (4, 6)), Position::new(4, 2), 1, 6),
+            3
+        );
+        assert_eq!(strategy.joystick(&state((4, 2), (4, 6))), Some(-1));
+    }
+
+    #[test]
+    fn test_evaluate() {
+        assert_eq!(
+            evaluate(SHORT_GAME, &mut FollowBall),
+            Ok(Evaluation {
+                score: 1,
+                inputs: 1,
+                moves: 1,
+                won: false
+            })
+        );
+    }
+}
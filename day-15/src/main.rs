@@ -1,8 +1,5 @@
+use std::env;
 use std::fs;
-use std::io::{self, Write};
-
-mod computer;
-use computer::*;
 
 mod robot;
 use robot::*;
@@ -14,36 +11,32 @@ fn main() {
     let contents = fs::read_to_string(filename)
         .unwrap_or_else(|_| panic!("Failed to read from file '{}'", filename));
 
-    let program: Vec<_> = contents
-        .split(',')
-        .map(|x| x.trim().parse::<i64>().unwrap())
-        .collect();
+    let program = intcode::parse_program(&contents)
+        .unwrap_or_else(|error| panic!("Failed to parse program: {}", error));
+
+    // --image <file> also saves the explored maze as a ppm, png or svg file
+    let image = env::args().skip_while(|arg| arg != "--image").nth(1);
 
-    process(program);
+    process(program, image);
 }
 
-fn process(program: Vec<i64>) {
-    let mut robot = RobotIO::new();
-    let mut computer = Computer::new(program, Some(&mut robot));
-    computer.run();
+// Pixels per position in saved images
+const CELL_SIZE: usize = 10;
+
+fn process(program: Vec<i64>, image: Option<String>) {
+    let mut world = explore(program).unwrap_or_else(|error| panic!("Program failed: {}", error));
 
-    let mut world = robot.get_world();
+    if let Some(path) = image {
+        world
+            .image()
+            .save(&path, CELL_SIZE)
+            .unwrap_or_else(|error| panic!("Failed to save image to '{}': {}", path, error));
+    }
 
     let mut count = 0;
 
     let minutes_required = loop {
-        print!("{}", termion::cursor::Save);
-
-        let (min_x, max_x, min_y, max_y) = world.limits();
-        for y in (min_y..=max_y).rev() {
-            for x in min_x..=max_x {
-                world.get(&Position::new(x, y)).draw();
-            }
-
-            println!("");
-            io::stdout().flush().unwrap();
-        }
-        print!("{}", termion::cursor::Restore);
+        world.draw(None);
 
         if world.oxygenate_step() {
             count += 1;
@@ -0,0 +1,232 @@
+use std::ops::RangeInclusive;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Position {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Position {
+    pub fn new(x: i64, y: i64) -> Position {
+        Position { x, y }
+    }
+
+    pub fn origin() -> Position {
+        Position::new(0, 0)
+    }
+
+    pub fn moved(&self, direction: Direction) -> Position {
+        self.moved_by(direction, 1)
+    }
+
+    pub fn moved_by(&self, direction: Direction, distance: i64) -> Position {
+        let (dx, dy) = direction.offset();
+        Position::new(self.x + dx * distance, self.y + dy * distance)
+    }
+
+    // The four adjacent positions, in the same order as Direction::ALL
+    pub fn neighbors(&self) -> [Position; 4] {
+        let mut neighbors = [*self; 4];
+        for (neighbor, direction) in neighbors.iter_mut().zip(&Direction::ALL) {
+            *neighbor = self.moved(*direction);
+        }
+
+        neighbors
+    }
+
+    pub fn manhattan_distance(&self, other: Position) -> i64 {
+        (self.x - other.x).abs() + (self.y - other.y).abs()
+    }
+}
+
+// Directions on the grid. Up is towards increasing y, which is only drawn at the top when y points
+// up (see Orientation).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Turn {
+    Left,
+    Right,
+}
+
+impl Direction {
+    // Clockwise, starting up
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+
+    pub fn offset(self) -> (i64, i64) {
+        match self {
+            Direction::Up => (0, 1),
+            Direction::Right => (1, 0),
+            Direction::Down => (0, -1),
+            Direction::Left => (-1, 0),
+        }
+    }
+
+    pub fn turned(self, turn: Turn) -> Direction {
+        let index = Direction::ALL.iter().position(|&d| d == self).unwrap();
+
+        match turn {
+            Turn::Right => Direction::ALL[(index + 1) % 4],
+            Turn::Left => Direction::ALL[(index + 3) % 4],
+        }
+    }
+
+    pub fn opposite(self) -> Direction {
+        self.turned(Turn::Right).turned(Turn::Right)
+    }
+}
+
+// Where something is on the grid and which way it is facing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Heading {
+    pub position: Position,
+    pub direction: Direction,
+}
+
+impl Heading {
+    pub fn new(position: Position, direction: Direction) -> Heading {
+        Heading {
+            position,
+            direction,
+        }
+    }
+
+    pub fn turn(&mut self, turn: Turn) {
+        self.direction = self.direction.turned(turn);
+    }
+
+    pub fn advance(&mut self, distance: i64) {
+        self.position = self.position.moved_by(self.direction, distance);
+    }
+
+    // The position right in front
+    pub fn ahead(&self) -> Position {
+        self.position.moved(self.direction)
+    }
+}
+
+// Smallest rectangle containing a set of positions, with both corners included
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Bounds {
+    pub min: Position,
+    pub max: Position,
+}
+
+impl Bounds {
+    pub fn new(min: Position, max: Position) -> Bounds {
+        Bounds { min, max }
+    }
+
+    // None if there are no positions
+    pub fn of<I: IntoIterator<Item = Position>>(positions: I) -> Option<Bounds> {
+        let mut positions = positions.into_iter();
+        let first = positions.next()?;
+
+        Some(
+            positions.fold(Bounds::new(first, first), |bounds, position| {
+                bounds.including(position)
+            }),
+        )
+    }
+
+    pub fn including(&self, position: Position) -> Bounds {
+        Bounds::new(
+            Position::new(self.min.x.min(position.x), self.min.y.min(position.y)),
+            Position::new(self.max.x.max(position.x), self.max.y.max(position.y)),
+        )
+    }
+
+    pub fn union(&self, other: &Bounds) -> Bounds {
+        self.including(other.min).including(other.max)
+    }
+
+    pub fn contains(&self, position: Position) -> bool {
+        self.xs().contains(&position.x) && self.ys().contains(&position.y)
+    }
+
+    pub fn xs(&self) -> RangeInclusive<i64> {
+        self.min.x..=self.max.x
+    }
+
+    pub fn ys(&self) -> RangeInclusive<i64> {
+        self.min.y..=self.max.y
+    }
+
+    pub fn width(&self) -> usize {
+        (self.max.x - self.min.x + 1) as usize
+    }
+
+    pub fn height(&self) -> usize {
+        (self.max.y - self.min.y + 1) as usize
+    }
+}
+
+#[cfg(test)]
+mod directions {
+    use super::*;
+
+    #[test]
+    fn turning() {
+        assert_eq!(Direction::Up.turned(Turn::Right), Direction::Right);
+        assert_eq!(Direction::Up.turned(Turn::Left), Direction::Left);
+        assert_eq!(Direction::Left.turned(Turn::Right), Direction::Up);
+        assert_eq!(Direction::Down.opposite(), Direction::Up);
+    }
+
+    #[test]
+    fn headings() {
+        let mut heading = Heading::new(Position::origin(), Direction::Up);
+        heading.advance(2);
+        heading.turn(Turn::Left);
+        heading.advance(3);
+
+        assert_eq!(heading.position, Position::new(-3, 2));
+        assert_eq!(heading.ahead(), Position::new(-4, 2));
+    }
+
+    #[test]
+    fn neighbors() {
+        assert_eq!(
+            Position::new(1, 1).neighbors(),
+            [
+                Position::new(1, 2),
+                Position::new(2, 1),
+                Position::new(1, 0),
+                Position::new(0, 1)
+            ]
+        );
+        assert_eq!(
+            Position::new(1, 1).manhattan_distance(Position::new(-2, 3)),
+            5
+        );
+    }
+
+    #[test]
+    fn bounds() {
+        let bounds = Bounds::of(vec![Position::new(2, -1), Position::new(-3, 4)]).unwrap();
+
+        assert_eq!(
+            bounds,
+            Bounds::new(Position::new(-3, -1), Position::new(2, 4))
+        );
+        assert_eq!((bounds.width(), bounds.height()), (6, 6));
+        assert!(bounds.contains(Position::origin()));
+        assert!(!bounds.contains(Position::new(3, 0)));
+        assert_eq!(Bounds::of(vec![]), None);
+        assert_eq!(
+            bounds.union(&Bounds::new(Position::new(0, 0), Position::new(5, 5))),
+            Bounds::new(Position::new(-3, -1), Position::new(5, 5))
+        );
+    }
+}
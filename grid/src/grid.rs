@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+use std::iter::FromIterator;
+
+use super::geometry::{Bounds, Position};
+
+// Cells at arbitrary (possibly negative) positions. Only cells that have been set are stored, so
+// the grid can grow in any direction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grid<T> {
+    cells: HashMap<Position, T>,
+}
+
+impl<T> Default for Grid<T> {
+    fn default() -> Grid<T> {
+        Grid {
+            cells: HashMap::new(),
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    pub fn new() -> Grid<T> {
+        Grid::default()
+    }
+
+    pub fn get(&self, position: Position) -> Option<&T> {
+        self.cells.get(&position)
+    }
+
+    pub fn get_mut(&mut self, position: Position) -> Option<&mut T> {
+        self.cells.get_mut(&position)
+    }
+
+    // Returns the cell's previous value, if any
+    pub fn insert(&mut self, position: Position, cell: T) -> Option<T> {
+        self.cells.insert(position, cell)
+    }
+
+    pub fn remove(&mut self, position: Position) -> Option<T> {
+        self.cells.remove(&position)
+    }
+
+    pub fn contains(&self, position: Position) -> bool {
+        self.cells.contains_key(&position)
+    }
+
+    // Number of cells that have been set
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    // Cells in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (Position, &T)> {
+        self.cells.iter().map(|(position, cell)| (*position, cell))
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = Position> + '_ {
+        self.cells.keys().copied()
+    }
+
+    // Position of any cell matching a predicate
+    pub fn find<P: Fn(&T) -> bool>(&self, predicate: P) -> Option<Position> {
+        self.iter()
+            .find(|(_, cell)| predicate(cell))
+            .map(|(position, _)| position)
+    }
+
+    // None if the grid is empty
+    pub fn bounds(&self) -> Option<Bounds> {
+        Bounds::of(self.positions())
+    }
+
+    // Adjacent cells that have been set, in the same order as Direction::ALL
+    pub fn neighbors(&self, position: Position) -> impl Iterator<Item = (Position, &T)> {
+        position
+            .neighbors()
+            .to_vec()
+            .into_iter()
+            .filter_map(move |neighbor| self.get(neighbor).map(|cell| (neighbor, cell)))
+    }
+
+    // Cells in a row within the grid's bounds, from left to right. Unset cells are None.
+    pub fn row(&self, y: i64) -> impl Iterator<Item = (Position, Option<&T>)> {
+        let xs = self.bounds().into_iter().flat_map(|bounds| bounds.xs());
+
+        xs.map(move |x| {
+            let position = Position::new(x, y);
+            (position, self.get(position))
+        })
+    }
+
+    // Cells in a column within the grid's bounds, from lowest to highest y. Unset cells are None.
+    pub fn column(&self, x: i64) -> impl Iterator<Item = (Position, Option<&T>)> {
+        let ys = self.bounds().into_iter().flat_map(|bounds| bounds.ys());
+
+        ys.map(move |y| {
+            let position = Position::new(x, y);
+            (position, self.get(position))
+        })
+    }
+}
+
+impl<T: Copy> Grid<T> {
+    // The cell at a position, or a default for unset cells
+    pub fn get_or(&self, position: Position, default: T) -> T {
+        self.get(position).copied().unwrap_or(default)
+    }
+}
+
+impl<T> FromIterator<(Position, T)> for Grid<T> {
+    fn from_iter<I: IntoIterator<Item = (Position, T)>>(cells: I) -> Grid<T> {
+        Grid {
+            cells: cells.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod cells {
+    use super::*;
+
+    fn grid() -> Grid<char> {
+        vec![
+            (Position::new(0, 0), 'a'),
+            (Position::new(1, 0), 'b'),
+            (Position::new(-1, 2), 'c'),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    fn access() {
+        let mut grid = grid();
+
+        assert_eq!(grid.get(Position::new(1, 0)), Some(&'b'));
+        assert_eq!(grid.get_or(Position::new(5, 5), '.'), '.');
+        assert_eq!(grid.insert(Position::new(1, 0), 'd'), Some('b'));
+        assert_eq!(grid.len(), 3);
+        assert_eq!(grid.find(|&cell| cell == 'c'), Some(Position::new(-1, 2)));
+        assert_eq!(grid.remove(Position::new(-1, 2)), Some('c'));
+        assert!(!grid.contains(Position::new(-1, 2)));
+    }
+
+    #[test]
+    fn bounds() {
+        assert_eq!(
+            grid().bounds(),
+            Some(Bounds::new(Position::new(-1, 0), Position::new(1, 2)))
+        );
+        assert_eq!(Grid::<char>::new().bounds(), None);
+    }
+
+    #[test]
+    fn neighbors() {
+        let grid = grid();
+        let neighbors: Vec<_> = grid.neighbors(Position::new(0, 0)).collect();
+
+        assert_eq!(neighbors, vec![(Position::new(1, 0), &'b')]);
+    }
+
+    #[test]
+    fn rows_and_columns() {
+        let grid = grid();
+
+        let row: Vec<_> = grid.row(0).map(|(_, cell)| cell.copied()).collect();
+        assert_eq!(row, vec![None, Some('a'), Some('b')]);
+
+        let column: Vec<_> = grid.column(-1).map(|(_, cell)| cell.copied()).collect();
+        assert_eq!(column, vec![None, None, Some('c')]);
+
+        assert_eq!(Grid::<char>::new().row(0).count(), 0);
+    }
+}
@@ -0,0 +1,440 @@
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use super::geometry::Position;
+use super::grid::Grid;
+use super::render::{Glyph, Orientation};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb(0, 0, 0);
+    pub const WHITE: Rgb = Rgb(255, 255, 255);
+
+    // As used in SVG and HTML, e.g. #ff8000
+    pub fn hex(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+}
+
+// Colors for the glyphs of cells, so that grids drawn as text can be drawn as images too
+#[derive(Debug, Clone, PartialEq)]
+pub struct Palette {
+    colors: HashMap<char, Rgb>,
+    background: Rgb,
+}
+
+impl Palette {
+    // Glyphs without a color of their own, as well as unset cells, are drawn with the background
+    pub fn new(background: Rgb) -> Palette {
+        Palette {
+            colors: HashMap::new(),
+            background,
+        }
+    }
+
+    pub fn set(&mut self, glyph: char, color: Rgb) {
+        self.colors.insert(glyph, color);
+    }
+
+    pub fn color(&self, glyph: char) -> Rgb {
+        self.colors.get(&glyph).copied().unwrap_or(self.background)
+    }
+
+    pub fn background(&self) -> Rgb {
+        self.background
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Ppm,
+    Png,
+    Svg,
+}
+
+impl Format {
+    pub fn from_path(path: &Path) -> Option<Format> {
+        match path.extension()?.to_str()? {
+            "ppm" => Some(Format::Ppm),
+            "png" => Some(Format::Png),
+            "svg" => Some(Format::Svg),
+            _ => None,
+        }
+    }
+}
+
+// Rectangle of pixels, stored row by row from the top left corner
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize, background: Rgb) -> Image {
+        Image {
+            width,
+            height,
+            pixels: vec![background; width * height],
+        }
+    }
+
+    // Builds an image from the color of each pixel, given its column and row
+    pub fn from_fn<F: Fn(usize, usize) -> Rgb>(width: usize, height: usize, color: F) -> Image {
+        Image {
+            width,
+            height,
+            pixels: (0..height)
+                .flat_map(|y| (0..width).map(move |x| (x, y)))
+                .map(|(x, y)| color(x, y))
+                .collect(),
+        }
+    }
+
+    // One pixel per cell within the grid's bounds, colored by a palette function. Unset cells are
+    // passed as None.
+    pub fn from_grid<T, F>(grid: &Grid<T>, orientation: Orientation, palette: F) -> Image
+    where
+        F: Fn(Option<&T>) -> Rgb,
+    {
+        let bounds = match grid.bounds() {
+            Some(bounds) => bounds,
+            None => return Image::new(0, 0, Rgb::BLACK),
+        };
+
+        Image::from_fn(bounds.width(), bounds.height(), |x, y| {
+            let y = match orientation {
+                Orientation::YDown => bounds.min.y + y as i64,
+                Orientation::YUp => bounds.max.y - y as i64,
+            };
+
+            palette(grid.get(Position::new(bounds.min.x + x as i64, y)))
+        })
+    }
+
+    // Same as from_grid, with cells colored by their glyphs
+    pub fn from_glyphs<T: Glyph>(
+        grid: &Grid<T>,
+        orientation: Orientation,
+        palette: &Palette,
+    ) -> Image {
+        Image::from_grid(grid, orientation, |cell: Option<&T>| {
+            cell.map_or(palette.background(), |cell| palette.color(cell.glyph()))
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Rgb {
+        self.pixels[y * self.width + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, color: Rgb) {
+        self.pixels[y * self.width + x] = color;
+    }
+
+    // Each pixel becomes a square of cell_size by cell_size pixels
+    pub fn scaled(&self, cell_size: usize) -> Image {
+        Image::from_fn(self.width * cell_size, self.height * cell_size, |x, y| {
+            self.get(x / cell_size, y / cell_size)
+        })
+    }
+
+    // Binary portable pixmap (P6)
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut ppm = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        for pixel in &self.pixels {
+            ppm.extend_from_slice(&[pixel.0, pixel.1, pixel.2]);
+        }
+
+        ppm
+    }
+
+    // 8-bit RGB PNG. The image data is stored uncompressed, which keeps the encoder small at the
+    // cost of larger files.
+    pub fn to_png(&self) -> Vec<u8> {
+        let mut png = vec![0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+        let mut header = Vec::new();
+        header.extend_from_slice(&(self.width as u32).to_be_bytes());
+        header.extend_from_slice(&(self.height as u32).to_be_bytes());
+        // Bit depth, color type (RGB), compression, filter and interlace methods
+        header.extend_from_slice(&[8, 2, 0, 0, 0]);
+        png_chunk(&mut png, b"IHDR", &header);
+
+        // Each row is preceded by its filter type, which is always none
+        let mut scanlines = Vec::with_capacity((1 + 3 * self.width) * self.height);
+        for row in self.pixels.chunks(self.width.max(1)).take(self.height) {
+            scanlines.push(0);
+            for pixel in row {
+                scanlines.extend_from_slice(&[pixel.0, pixel.1, pixel.2]);
+            }
+        }
+        png_chunk(&mut png, b"IDAT", &zlib_stored(&scanlines));
+
+        png_chunk(&mut png, b"IEND", &[]);
+        png
+    }
+
+    // Vector image with a square of cell_size units per pixel. Runs of equal pixels in a row are
+    // merged into a single rectangle.
+    pub fn to_svg(&self, cell_size: usize) -> String {
+        let (width, height) = (self.width * cell_size, self.height * cell_size);
+        let mut svg = String::new();
+
+        writeln!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" \
+             viewBox=\"0 0 {} {}\" shape-rendering=\"crispEdges\">",
+            width, height, width, height
+        )
+        .unwrap();
+
+        for y in 0..self.height {
+            let mut x = 0;
+            while x < self.width {
+                let color = self.get(x, y);
+                let run = (x..self.width)
+                    .take_while(|&end| self.get(end, y) == color)
+                    .count();
+
+                writeln!(
+                    svg,
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+                    x * cell_size,
+                    y * cell_size,
+                    run * cell_size,
+                    cell_size,
+                    color.hex()
+                )
+                .unwrap();
+
+                x += run;
+            }
+        }
+
+        writeln!(svg, "</svg>").unwrap();
+        svg
+    }
+
+    // Writes the image in the format given by the file's extension (ppm, png or svg)
+    pub fn save<P: AsRef<Path>>(&self, path: P, cell_size: usize) -> io::Result<()> {
+        let path = path.as_ref();
+
+        let contents = match Format::from_path(path) {
+            Some(Format::Ppm) => self.scaled(cell_size).to_ppm(),
+            Some(Format::Png) => self.scaled(cell_size).to_png(),
+            Some(Format::Svg) => self.to_svg(cell_size).into_bytes(),
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("unknown image format for '{}'", path.display()),
+                ))
+            }
+        };
+
+        fs::write(path, contents)
+    }
+}
+
+// Length, type, data and CRC of the type and data
+fn png_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0, |crc, &byte| {
+        (0..8).fold(crc ^ u32::from(byte), |crc, _| {
+            if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            }
+        })
+    })
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (a, b) = bytes.iter().fold((1, 0), |(a, b), &byte| {
+        let a = (a + u32::from(byte)) % 65521;
+        (a, (b + a) % 65521)
+    });
+
+    (b << 16) | a
+}
+
+// Zlib stream made of uncompressed deflate blocks, which can hold up to 65535 bytes each
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut zlib = vec![0x78, 0x01];
+    let blocks: Vec<_> = data.chunks(0xffff).collect();
+
+    if blocks.is_empty() {
+        zlib.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+
+    for (index, block) in blocks.iter().enumerate() {
+        let last = index + 1 == blocks.len();
+        let length = block.len() as u16;
+
+        zlib.push(last as u8);
+        zlib.extend_from_slice(&length.to_le_bytes());
+        zlib.extend_from_slice(&(!length).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+
+    zlib.extend_from_slice(&adler32(data).to_be_bytes());
+    zlib
+}
+
+#[cfg(test)]
+mod images {
+    use super::*;
+
+    enum Tile {
+        Wall,
+        Floor,
+    }
+
+    impl Glyph for Tile {
+        fn glyph(&self) -> char {
+            match self {
+                Tile::Wall => '#',
+                Tile::Floor => '.',
+            }
+        }
+    }
+
+    fn checkerboard() -> Image {
+        Image::from_fn(2, 2, |x, y| {
+            if (x + y) % 2 == 0 {
+                Rgb::WHITE
+            } else {
+                Rgb(255, 0, 0)
+            }
+        })
+    }
+
+    #[test]
+    fn from_grid() {
+        let grid: Grid<bool> = vec![(Position::new(0, 0), true), (Position::new(1, 1), false)]
+            .into_iter()
+            .collect();
+        let palette = |cell: Option<&bool>| match cell {
+            Some(true) => Rgb::WHITE,
+            Some(false) => Rgb(0, 0, 255),
+            None => Rgb::BLACK,
+        };
+
+        let image = Image::from_grid(&grid, Orientation::YDown, palette);
+        assert_eq!(image.get(0, 0), Rgb::WHITE);
+        assert_eq!(image.get(1, 0), Rgb::BLACK);
+        assert_eq!(image.get(1, 1), Rgb(0, 0, 255));
+
+        let image = Image::from_grid(&grid, Orientation::YUp, palette);
+        assert_eq!(image.get(0, 1), Rgb::WHITE);
+        assert_eq!(image.get(1, 0), Rgb(0, 0, 255));
+    }
+
+    #[test]
+    fn palettes() {
+        let grid: Grid<Tile> = vec![
+            (Position::new(0, 0), Tile::Wall),
+            (Position::new(2, 0), Tile::Floor),
+        ]
+        .into_iter()
+        .collect();
+        let mut palette = Palette::new(Rgb(0, 0, 255));
+        palette.set('#', Rgb::WHITE);
+
+        let image = Image::from_glyphs(&grid, Orientation::YDown, &palette);
+        assert_eq!(image.get(0, 0), Rgb::WHITE);
+        assert_eq!(image.get(1, 0), Rgb(0, 0, 255));
+        assert_eq!(image.get(2, 0), Rgb(0, 0, 255));
+    }
+
+    #[test]
+    fn scaling() {
+        let image = checkerboard().scaled(3);
+
+        assert_eq!((image.width(), image.height()), (6, 6));
+        assert_eq!(image.get(2, 2), Rgb::WHITE);
+        assert_eq!(image.get(3, 2), Rgb(255, 0, 0));
+    }
+
+    #[test]
+    fn ppm() {
+        let mut expected = b"P6\n2 2\n255\n".to_vec();
+        expected.extend_from_slice(&[255, 255, 255, 255, 0, 0, 255, 0, 0, 255, 255, 255]);
+
+        assert_eq!(checkerboard().to_ppm(), expected);
+    }
+
+    #[test]
+    fn png() {
+        let png = checkerboard().to_png();
+
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(&png[8..16], b"\x00\x00\x00\x0dIHDR");
+        assert_eq!(&png[16..29], &[0, 0, 0, 2, 0, 0, 0, 2, 8, 2, 0, 0, 0]);
+        // The IEND chunk always has the same CRC
+        assert_eq!(
+            &png[png.len() - 12..],
+            b"\x00\x00\x00\x00IEND\xae\x42\x60\x82"
+        );
+    }
+
+    #[test]
+    fn checksums() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+
+    #[test]
+    fn svg() {
+        let image = Image::from_fn(3, 1, |x, _| if x < 2 { Rgb::BLACK } else { Rgb::WHITE });
+
+        assert_eq!(
+            image.to_svg(10),
+            [
+                "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"30\" height=\"10\" \
+                 viewBox=\"0 0 30 10\" shape-rendering=\"crispEdges\">",
+                "<rect x=\"0\" y=\"0\" width=\"20\" height=\"10\" fill=\"#000000\"/>",
+                "<rect x=\"20\" y=\"0\" width=\"10\" height=\"10\" fill=\"#ffffff\"/>",
+                "</svg>",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn formats() {
+        assert_eq!(Format::from_path(Path::new("hull.png")), Some(Format::Png));
+        assert_eq!(
+            Format::from_path(Path::new("out/maze.svg")),
+            Some(Format::Svg)
+        );
+        assert_eq!(Format::from_path(Path::new("board.ppm")), Some(Format::Ppm));
+        assert_eq!(Format::from_path(Path::new("board.txt")), None);
+        assert_eq!(Format::from_path(Path::new("board")), None);
+    }
+}
@@ -0,0 +1,14 @@
+pub mod geometry;
+pub use geometry::{Bounds, Direction, Heading, Position, Turn};
+
+pub mod grid;
+pub use crate::grid::Grid;
+
+pub mod render;
+pub use render::{Glyph, Orientation, Renderer};
+
+pub mod image;
+pub use image::{Format, Image, Palette, Rgb};
+
+pub mod ocr;
+pub use ocr::UnknownGlyph;
@@ -0,0 +1,214 @@
+use std::error::Error;
+use std::fmt;
+
+use super::geometry::{Bounds, Position};
+use super::grid::Grid;
+use super::render::Orientation;
+
+// Capital letters as drawn by Advent of Code puzzles, six pixels tall and (mostly) four wide
+const FONT: [(char, [&str; 6]); 18] = [
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', ["###", ".#.", ".#.", ".#.", ".#.", "###"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Y', ["#...#", "#...#", ".#.#.", "..#..", "..#..", "..#.."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+// Characters are drawn in cells this many columns wide, which leaves a blank column after all of
+// them but the widest ones
+const CELL_WIDTH: i64 = 5;
+
+// A character that is not in the font. It carries its position in the text and its pixels, drawn
+// with '#' and '.' as in the font.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnknownGlyph {
+    pub index: usize,
+    pub rows: Vec<String>,
+}
+
+impl fmt::Display for UnknownGlyph {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "unknown glyph at character {}:", self.index)?;
+        for row in &self.rows {
+            writeln!(f, "{}", row)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Error for UnknownGlyph {}
+
+// Reads the line of text spelled by a grid's lit cells. Unset cells are not lit. The text can start
+// anywhere in the grid, and each character takes up a fixed number of columns from there on.
+pub fn read<T, F>(grid: &Grid<T>, orientation: Orientation, lit: F) -> Result<String, UnknownGlyph>
+where
+    F: Fn(&T) -> bool,
+{
+    let pixels: Grid<()> = grid
+        .iter()
+        .filter(|(_, cell)| lit(cell))
+        .map(|(position, _)| (position, ()))
+        .collect();
+
+    let bounds = match pixels.bounds() {
+        Some(bounds) => bounds,
+        None => return Ok(String::new()),
+    };
+
+    let ys: Vec<_> = match orientation {
+        Orientation::YDown => bounds.ys().collect(),
+        Orientation::YUp => bounds.ys().rev().collect(),
+    };
+
+    let blank = |x: i64| !ys.iter().any(|&y| pixels.contains(Position::new(x, y)));
+
+    let mut text = String::new();
+    for start in (bounds.min.x..=bounds.max.x).step_by(CELL_WIDTH as usize) {
+        // Most characters are narrower than their cell
+        let end = match (start..start + CELL_WIDTH).rev().find(|&x| !blank(x)) {
+            Some(end) => end,
+            None => continue,
+        };
+
+        let area = Bounds::new(
+            Position::new(start, bounds.min.y),
+            Position::new(end, bounds.max.y),
+        );
+        let rows: Vec<String> = ys
+            .iter()
+            .map(|&y| {
+                area.xs()
+                    .map(|x| {
+                        if pixels.contains(Position::new(x, y)) {
+                            '#'
+                        } else {
+                            '.'
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        match FONT.iter().find(|(_, glyph)| glyph[..] == rows[..]) {
+            Some((letter, _)) => text.push(*letter),
+            None => {
+                return Err(UnknownGlyph {
+                    index: text.len(),
+                    rows,
+                })
+            }
+        }
+    }
+
+    Ok(text)
+}
+
+#[cfg(test)]
+mod reading {
+    use super::*;
+
+    // Lit cells are drawn as '#', starting at the top left corner
+    fn grid(rows: &[&str]) -> Grid<bool> {
+        rows.iter()
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.chars()
+                    .enumerate()
+                    .map(move |(x, pixel)| (Position::new(x as i64, y as i64), pixel == '#'))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn letters() {
+        let grid = grid(&[
+            "..#..#.####.#...#......",
+            "..#..#.#....#...#......",
+            "..####.###...#.#.......",
+            "..#..#.#......#........",
+            "..#..#.#......#........",
+            "..#..#.####...#........",
+        ]);
+
+        assert_eq!(
+            read(&grid, Orientation::YDown, |&lit| lit),
+            Ok("HEY".to_string())
+        );
+        assert_eq!(
+            read(&Grid::<bool>::new(), Orientation::YDown, |&lit| lit),
+            Ok(String::new())
+        );
+    }
+
+    #[test]
+    fn wide_letters() {
+        // Y takes up its whole cell, leaving no blank column before the next letter
+        let grid = grid(&[
+            ".##..#...#####",
+            "#..#.#...##...",
+            "#..#..#.#.###.",
+            "####...#..#...",
+            "#..#...#..#...",
+            "#..#...#..####",
+        ]);
+
+        assert_eq!(
+            read(&grid, Orientation::YDown, |&lit| lit),
+            Ok("AYE".to_string())
+        );
+    }
+
+    #[test]
+    fn upside_down() {
+        let mut rows = [
+            "###..#...",
+            "#..#.#...",
+            "###..#...",
+            "#..#.#...",
+            "#..#.#...",
+            "###..####",
+        ];
+        rows.reverse();
+
+        assert_eq!(
+            read(&grid(&rows), Orientation::YUp, |&lit| lit),
+            Ok("BL".to_string())
+        );
+    }
+
+    #[test]
+    fn unknown_glyphs() {
+        let rows = [
+            ".##..###", "#..#.#..", "#....#..", "#.....##", "#..#...#", ".##..###",
+        ];
+
+        assert_eq!(
+            read(&grid(&rows), Orientation::YDown, |&lit| lit),
+            Err(UnknownGlyph {
+                index: 1,
+                rows: vec![
+                    "###".to_string(),
+                    "#..".to_string(),
+                    "#..".to_string(),
+                    ".##".to_string(),
+                    "..#".to_string(),
+                    "###".to_string()
+                ],
+            })
+        );
+    }
+}
@@ -0,0 +1,155 @@
+use super::geometry::{Bounds, Position};
+use super::grid::Grid;
+
+// Cells that know how to draw themselves
+pub trait Glyph {
+    fn glyph(&self) -> char;
+}
+
+// Which way y grows when drawn. Screens usually have it grow downwards, while maps built by moving
+// up and down have it grow upwards.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Orientation {
+    YDown,
+    YUp,
+}
+
+type GlyphFn<'r, T> = Box<dyn Fn(Option<&T>) -> char + 'r>;
+
+// Draws a grid as text, one line per row, turning cells into characters with a function of choice.
+// Unset cells are passed as None.
+pub struct Renderer<'r, T> {
+    glyph: GlyphFn<'r, T>,
+    orientation: Orientation,
+    area: Option<Bounds>,
+}
+
+impl<'r, T> Renderer<'r, T> {
+    pub fn new<F>(glyph: F) -> Renderer<'r, T>
+    where
+        F: Fn(Option<&T>) -> char + 'r,
+    {
+        Renderer {
+            glyph: Box::new(glyph),
+            orientation: Orientation::YDown,
+            area: None,
+        }
+    }
+
+    pub fn set_orientation(&mut self, orientation: Orientation) {
+        self.orientation = orientation;
+    }
+
+    // Always draws at least this area, even if the grid's cells do not reach it
+    pub fn set_area(&mut self, area: Bounds) {
+        self.area = Some(area);
+    }
+
+    pub fn render(&self, grid: &Grid<T>) -> String {
+        self.render_with_markers(grid, &[])
+    }
+
+    // Same as render, but with some positions drawn as markers (e.g. a moving robot) instead of
+    // their cells
+    pub fn render_with_markers(&self, grid: &Grid<T>, markers: &[(Position, char)]) -> String {
+        let bounds = match (grid.bounds(), self.area) {
+            (Some(bounds), Some(area)) => bounds.union(&area),
+            (bounds, area) => match bounds.or(area) {
+                Some(bounds) => bounds,
+                None => return String::new(),
+            },
+        };
+
+        let ys: Vec<_> = match self.orientation {
+            Orientation::YDown => bounds.ys().collect(),
+            Orientation::YUp => bounds.ys().rev().collect(),
+        };
+
+        let mut text = String::with_capacity((bounds.width() + 1) * bounds.height());
+        for y in ys {
+            for x in bounds.xs() {
+                let position = Position::new(x, y);
+
+                text.push(
+                    match markers.iter().find(|(marker, _)| *marker == position) {
+                        Some((_, glyph)) => *glyph,
+                        None => (self.glyph)(grid.get(position)),
+                    },
+                );
+            }
+
+            text.push('\n');
+        }
+
+        text
+    }
+}
+
+impl<'r, T: Glyph> Renderer<'r, T> {
+    // Draws cells with their own glyphs, and unset cells as blanks
+    pub fn with_glyphs() -> Renderer<'r, T> {
+        Renderer::new(|cell: Option<&T>| cell.map_or(' ', Glyph::glyph))
+    }
+}
+
+#[cfg(test)]
+mod rendering {
+    use super::*;
+
+    #[derive(Clone, Copy)]
+    enum Tile {
+        Wall,
+        Floor,
+    }
+
+    impl Glyph for Tile {
+        fn glyph(&self) -> char {
+            match self {
+                Tile::Wall => '#',
+                Tile::Floor => '.',
+            }
+        }
+    }
+
+    fn grid() -> Grid<Tile> {
+        vec![
+            (Position::new(0, 0), Tile::Wall),
+            (Position::new(1, 0), Tile::Floor),
+            (Position::new(2, 1), Tile::Wall),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    fn orientation() {
+        let mut renderer = Renderer::with_glyphs();
+        assert_eq!(renderer.render(&grid()), "#. \n  #\n");
+
+        renderer.set_orientation(Orientation::YUp);
+        assert_eq!(renderer.render(&grid()), "  #\n#. \n");
+    }
+
+    #[test]
+    fn custom_glyphs_and_markers() {
+        let renderer = Renderer::new(|cell: Option<&Tile>| match cell {
+            Some(Tile::Wall) => 'W',
+            _ => '?',
+        });
+
+        assert_eq!(
+            renderer.render_with_markers(&grid(), &[(Position::new(1, 1), '@')]),
+            "W??\n?@W\n"
+        );
+    }
+
+    #[test]
+    fn minimum_area() {
+        let mut renderer = Renderer::with_glyphs();
+        renderer.set_area(Bounds::new(Position::new(-1, 0), Position::new(0, 0)));
+
+        assert_eq!(renderer.render(&grid()), " #. \n   #\n");
+        assert_eq!(renderer.render(&Grid::new()), "  \n");
+        assert_eq!(Renderer::<Tile>::with_glyphs().render(&Grid::new()), "");
+    }
+}
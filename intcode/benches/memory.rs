@@ -0,0 +1,56 @@
+use std::collections::HashMap;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use intcode::*;
+
+// Adds up all numbers from the input down to one, only touching the program image
+const SUM: &str = "
+          in   [n]
+    loop: add  [sum], [n], [sum]
+          add  [n], #-1, [n]
+          jt   [n], #loop
+          out  [sum]
+          hlt
+    n:    data 0
+    sum:  data 0
+";
+
+// Writes the input down to one far past the end of the program, one cell at a time
+const FILL: &str = "
+          in   [n]
+          arb  #100000
+    loop: add  [n], #0, rb+0
+          arb  #1
+          add  [n], #-1, [n]
+          jt   [n], #loop
+          out  rb-1
+          hlt
+    n:    data 0
+";
+
+fn run<M: Memory>(program: &[Value], input: Value) -> Value {
+    let mut computer = Computer::<M>::with_memory(program.to_vec(), None);
+    computer.provide_input(input);
+
+    match computer.run_until_event() {
+        Ok(Event::Output(value)) => value,
+        event => panic!("Unexpected event {:?}", event),
+    }
+}
+
+fn backends(c: &mut Criterion) {
+    for (name, source) in &[("sum", SUM), ("fill", FILL)] {
+        let program = assembler::assemble(source).unwrap();
+
+        c.bench_function(&format!("{} hash map", name), |b| {
+            b.iter(|| run::<HashMap<Address, Value>>(&program, black_box(100_000)))
+        });
+        c.bench_function(&format!("{} paged", name), |b| {
+            b.iter(|| run::<PagedMemory>(&program, black_box(100_000)))
+        });
+    }
+}
+
+criterion_group!(benches, backends);
+criterion_main!(benches);
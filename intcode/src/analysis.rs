@@ -0,0 +1,467 @@
+use core::convert::TryFrom;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::ops::Range;
+
+use super::disassembler::{self, Item, Operand};
+use super::hardware::*;
+use super::instruction::*;
+
+// Where execution may continue after a block
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Successor {
+    Address(Address),
+    // Jump whose target is only known at run time (i.e. not an immediate)
+    Unknown,
+}
+
+// Straight-line run of instructions: only the first one is jumped to, and only the last one jumps
+#[derive(Debug, Clone, PartialEq)]
+pub struct Block {
+    pub start: Address,
+    pub items: Vec<Item>,
+    pub successors: Vec<Successor>,
+}
+
+impl Block {
+    // Address right after the block's last word
+    pub fn end(&self) -> Address {
+        self.items
+            .last()
+            .map_or(self.start, |item| item.address() + item.size())
+    }
+}
+
+// An instruction writing to an address that holds (part of) a reachable instruction
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SelfModification {
+    pub instruction: Address,
+    pub target: Address,
+}
+
+// Control-flow graph of a program, built by following execution from address zero. Only jumps with
+// immediate targets can be followed: the rest become unknown edges, so code reached through them
+// is missing from the graph.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Analysis {
+    // Sorted by start address
+    pub blocks: Vec<Block>,
+    // Addresses execution may reach that do not hold a valid instruction
+    pub invalid: BTreeSet<Address>,
+    // Addresses read or written by reachable instructions through indexed operands
+    pub data: BTreeSet<Address>,
+    pub self_modifications: Vec<SelfModification>,
+    // Words that are neither reachable code nor data. If the graph has unknown edges these may
+    // still be executed.
+    pub unreachable: Vec<Range<Address>>,
+}
+
+// Where execution may continue after an instruction: the jump target comes before the next
+// instruction
+fn successors(item: &Item) -> Vec<Successor> {
+    let (address, instruction, operands) = match item {
+        Item::Instruction {
+            address,
+            instruction,
+            operands,
+            ..
+        } => (*address, *instruction, operands),
+        Item::Data { .. } => return Vec::new(),
+    };
+
+    let next = Successor::Address(address + item.size());
+
+    let jump_if = match instruction {
+        Instruction::Halt => return Vec::new(),
+        Instruction::JumpIfTrue => true,
+        Instruction::JumpIfFalse => false,
+        _ => return vec![next],
+    };
+
+    let target = match operands[1] {
+        Operand {
+            mode: ArgumentMode::Immediate,
+            value,
+        } => match Address::try_from(value) {
+            Ok(target) => Successor::Address(target),
+            // Jumping to a negative address fails
+            Err(_) => return vec![next],
+        },
+        _ => Successor::Unknown,
+    };
+
+    // Immediate conditions always go the same way, as in 'jt #1, #label'
+    match operands[0] {
+        Operand {
+            mode: ArgumentMode::Immediate,
+            value,
+        } => {
+            if (value != 0) == jump_if {
+                vec![target]
+            } else {
+                vec![next]
+            }
+        }
+        _ => vec![target, next],
+    }
+}
+
+fn is_branch(item: &Item) -> bool {
+    match item {
+        Item::Instruction { instruction, .. } => matches!(
+            instruction,
+            Instruction::JumpIfTrue | Instruction::JumpIfFalse | Instruction::Halt
+        ),
+        Item::Data { .. } => true,
+    }
+}
+
+pub fn analyze(program: &[Value]) -> Analysis {
+    // Follow execution, decoding each reachable instruction once
+    let mut instructions = BTreeMap::new();
+    let mut invalid = BTreeSet::new();
+    let mut leaders = BTreeSet::new();
+    let mut pending = vec![0];
+    leaders.insert(0);
+
+    while let Some(address) = pending.pop() {
+        if instructions.contains_key(&address) || invalid.contains(&address) {
+            continue;
+        }
+
+        let item = match disassembler::decode(program, address) {
+            Some(item) => item,
+            None => {
+                invalid.insert(address);
+                continue;
+            }
+        };
+
+        for successor in successors(&item) {
+            if let Successor::Address(target) = successor {
+                // Blocks start at jump targets and right after conditional jumps
+                if is_branch(&item) {
+                    leaders.insert(target);
+                }
+
+                pending.push(target);
+            }
+        }
+
+        instructions.insert(address, item);
+    }
+
+    let blocks = leaders
+        .iter()
+        .filter(|address| instructions.contains_key(address))
+        .map(|&start| {
+            let mut items = Vec::new();
+            let mut address = start;
+
+            loop {
+                let item = instructions[&address].clone();
+                let next = address + item.size();
+                let last = is_branch(&item) || leaders.contains(&next);
+
+                items.push(item);
+
+                if last || !instructions.contains_key(&next) {
+                    break;
+                }
+
+                address = next;
+            }
+
+            let successors = successors(items.last().unwrap());
+            Block {
+                start,
+                items,
+                successors,
+            }
+        })
+        .collect();
+
+    let code: BTreeSet<Address> = instructions
+        .values()
+        .flat_map(|item| item.address()..item.address() + item.size())
+        .collect();
+
+    let mut data = BTreeSet::new();
+    let mut self_modifications = Vec::new();
+
+    for item in instructions.values() {
+        if let Item::Instruction {
+            address,
+            instruction,
+            operands,
+            ..
+        } = item
+        {
+            for (argument_type, operand) in instruction.argument_types().iter().zip(operands) {
+                if operand.mode != ArgumentMode::Indexed {
+                    continue;
+                }
+
+                // The disassembler rejects negative indexed operands
+                let target = Address::try_from(operand.value).unwrap();
+                data.insert(target);
+
+                if *argument_type == ArgumentType::Out && code.contains(&target) {
+                    self_modifications.push(SelfModification {
+                        instruction: *address,
+                        target,
+                    });
+                }
+            }
+        }
+    }
+
+    let mut unreachable: Vec<Range<Address>> = Vec::new();
+    for address in (0..program.len()).filter(|a| !code.contains(a) && !data.contains(a)) {
+        match unreachable.last_mut() {
+            Some(range) if range.end == address => range.end += 1,
+            _ => unreachable.push(address..address + 1),
+        }
+    }
+
+    Analysis {
+        blocks,
+        invalid,
+        data,
+        self_modifications,
+        unreachable,
+    }
+}
+
+impl Analysis {
+    // Graphviz description of the control-flow graph, with each block listing its instructions
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::new();
+        let has_block = |address| self.blocks.iter().any(|block| block.start == address);
+
+        writeln!(dot, "digraph program {{").unwrap();
+        writeln!(dot, "    node [shape=box, fontname=monospace];").unwrap();
+
+        for block in &self.blocks {
+            let label: String = block
+                .items
+                .iter()
+                .map(|item| format!("{}\\l", item))
+                .collect();
+            writeln!(dot, "    b{} [label=\"{}\"];", block.start, label).unwrap();
+        }
+
+        for &address in &self.invalid {
+            writeln!(
+                dot,
+                "    b{} [label=\"{}: invalid\", color=red];",
+                address, address
+            )
+            .unwrap();
+        }
+
+        if self.has_unknown_edges() {
+            writeln!(dot, "    unknown [label=\"?\", shape=circle];").unwrap();
+        }
+
+        for block in &self.blocks {
+            for successor in &block.successors {
+                match successor {
+                    Successor::Address(target)
+                        if has_block(*target) || self.invalid.contains(target) =>
+                    {
+                        writeln!(dot, "    b{} -> b{};", block.start, target).unwrap()
+                    }
+                    Successor::Address(_) => (),
+                    Successor::Unknown => {
+                        writeln!(dot, "    b{} -> unknown [style=dashed];", block.start).unwrap()
+                    }
+                }
+            }
+        }
+
+        writeln!(dot, "}}").unwrap();
+        dot
+    }
+
+    pub fn has_unknown_edges(&self) -> bool {
+        self.blocks
+            .iter()
+            .any(|block| block.successors.contains(&Successor::Unknown))
+    }
+
+    // Human readable summary of everything but the graph itself
+    pub fn report(&self) -> String {
+        let mut report = String::new();
+        let ranges = |ranges: &mut dyn Iterator<Item = Range<Address>>| {
+            let ranges: Vec<_> = ranges
+                .map(|range| match range.len() {
+                    1 => range.start.to_string(),
+                    _ => format!("{}-{}", range.start, range.end - 1),
+                })
+                .collect();
+
+            if ranges.is_empty() {
+                "none".to_string()
+            } else {
+                ranges.join(", ")
+            }
+        };
+
+        writeln!(report, "Basic blocks: {}", self.blocks.len()).unwrap();
+        writeln!(
+            report,
+            "Unknown jump targets: {}",
+            if self.has_unknown_edges() {
+                "yes"
+            } else {
+                "no"
+            }
+        )
+        .unwrap();
+        writeln!(
+            report,
+            "Invalid instructions at: {}",
+            ranges(&mut self.invalid.iter().map(|&a| a..a + 1))
+        )
+        .unwrap();
+        writeln!(
+            report,
+            "Data addresses: {}",
+            ranges(&mut self.data.iter().map(|&a| a..a + 1))
+        )
+        .unwrap();
+        writeln!(
+            report,
+            "Unreachable code: {}",
+            ranges(&mut self.unreachable.iter().cloned())
+        )
+        .unwrap();
+
+        for modification in &self.self_modifications {
+            writeln!(
+                report,
+                "Instruction at {} writes into code at {}",
+                modification.instruction, modification.target
+            )
+            .unwrap();
+        }
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod analyzing {
+    use super::*;
+    use crate::assembler::assemble;
+
+    fn starts(analysis: &Analysis) -> Vec<Address> {
+        analysis.blocks.iter().map(|block| block.start).collect()
+    }
+
+    #[test]
+    fn straight_line() {
+        let analysis = analyze(&[1, 0, 0, 5, 99, 0]);
+
+        assert_eq!(starts(&analysis), vec![0]);
+        assert_eq!(analysis.blocks[0].end(), 5);
+        assert_eq!(analysis.blocks[0].successors, vec![]);
+        assert_eq!(analysis.data, vec![0, 5].into_iter().collect());
+        assert!(analysis.unreachable.is_empty());
+    }
+
+    #[test]
+    fn conditional_jumps() {
+        let program = assemble(
+            "
+                  in   [x]
+            loop: add  [x], #-1, [x]
+                  jt   [x], #loop
+                  out  [x]
+                  hlt
+            x:    data 0
+            ",
+        )
+        .unwrap();
+        let analysis = analyze(&program);
+
+        assert_eq!(starts(&analysis), vec![0, 2, 9]);
+        assert_eq!(analysis.blocks[0].successors, vec![Successor::Address(2)]);
+        assert_eq!(
+            analysis.blocks[1].successors,
+            vec![Successor::Address(2), Successor::Address(9)]
+        );
+        assert_eq!(analysis.blocks[2].successors, vec![]);
+        assert!(!analysis.has_unknown_edges());
+        assert_eq!(analysis.data, vec![12].into_iter().collect());
+    }
+
+    #[test]
+    fn constant_conditions_and_unreachable_code() {
+        let program = assemble(
+            "
+                  jt   #1, #end
+                  out  #1
+                  out  #2
+            end:  hlt
+            ",
+        )
+        .unwrap();
+        let analysis = analyze(&program);
+
+        assert_eq!(starts(&analysis), vec![0, 7]);
+        assert_eq!(analysis.blocks[0].successors, vec![Successor::Address(7)]);
+        assert_eq!(analysis.unreachable, vec![3..7]);
+    }
+
+    #[test]
+    fn indirect_jumps() {
+        // Jumps to a target read from memory, and runs off the end of the program
+        let analysis = analyze(&[1105, 1, 3, 6, 7, 8, 4, 0, 0]);
+
+        assert_eq!(starts(&analysis), vec![0, 3, 6]);
+        assert_eq!(
+            analysis.blocks[1].successors,
+            vec![Successor::Unknown, Successor::Address(6)]
+        );
+        assert!(analysis.has_unknown_edges());
+        assert_eq!(analysis.invalid, vec![8].into_iter().collect());
+    }
+
+    #[test]
+    fn self_modification() {
+        // Patches the add into a multiplication
+        let analysis = analyze(&[1101, 2, 0, 4, 1, 5, 5, 9, 99]);
+
+        assert_eq!(
+            analysis.self_modifications,
+            vec![SelfModification {
+                instruction: 0,
+                target: 4
+            }]
+        );
+    }
+
+    #[test]
+    fn dot_export() {
+        let analysis = analyze(&[1006, 0, 4, 99, 1105, 1, 0, 5, 7, 8]);
+
+        assert_eq!(
+            analysis.to_dot(),
+            [
+                "digraph program {",
+                "    node [shape=box, fontname=monospace];",
+                "    b0 [label=\"     0: jf   [0], #4\\l\"];",
+                "    b3 [label=\"     3: hlt\\l\"];",
+                "    b4 [label=\"     4: jt   #1, #0\\l\"];",
+                "    b0 -> b4;",
+                "    b0 -> b3;",
+                "    b4 -> b0;",
+                "}",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+}
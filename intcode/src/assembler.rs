@@ -0,0 +1,439 @@
+use core::convert::TryFrom;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+use super::hardware::*;
+use super::instruction::*;
+use super::memory::{Memory, PagedMemory};
+use super::registry::Registry;
+
+// Assembles programs written in the same syntax the disassembler produces:
+//
+//   loop:   in   [n]              ; comments start with a semicolon
+//           jf   [n], #end        ; labels can be used wherever a number can
+//           out  rb-1
+//           hlt
+//   n:      data 0, 1, 2
+//
+// Operands are immediate (#5), indexed ([12]) or relative (rb+3). Numeric labels (such as the
+// addresses in a disassembler listing) are not defined, but checked against the current address.
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AssemblyErrorKind {
+    UnknownMnemonic(String),
+    WrongArgumentCount { expected: usize, found: usize },
+    InvalidOperand(String),
+    ImmediateWrite { argument: usize },
+    InvalidLabel(String),
+    DuplicateLabel(String),
+    UndefinedLabel(String),
+    AddressMismatch { expected: Address, found: Address },
+}
+
+// Lines are 1-based, as shown by text editors
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssemblyError {
+    pub line: usize,
+    pub kind: AssemblyErrorKind,
+}
+
+impl fmt::Display for AssemblyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use AssemblyErrorKind::*;
+
+        write!(f, "line {}: ", self.line)?;
+
+        match &self.kind {
+            UnknownMnemonic(mnemonic) => write!(f, "unknown mnemonic '{}'", mnemonic),
+            WrongArgumentCount { expected, found } => {
+                write!(f, "expected {} arguments, found {}", expected, found)
+            }
+            InvalidOperand(operand) => write!(f, "invalid operand '{}'", operand),
+            ImmediateWrite { argument } => {
+                write!(
+                    f,
+                    "argument {} is written to and cannot be immediate",
+                    argument
+                )
+            }
+            InvalidLabel(label) => write!(f, "invalid label '{}'", label),
+            DuplicateLabel(label) => write!(f, "label '{}' is already defined", label),
+            UndefinedLabel(label) => write!(f, "label '{}' is not defined", label),
+            AddressMismatch { expected, found } => write!(
+                f,
+                "address {} was expected, but this is address {}",
+                expected, found
+            ),
+        }
+    }
+}
+
+impl Error for AssemblyError {}
+
+// Either a literal number or a reference to a label, resolved once all labels are known
+#[derive(Debug)]
+enum Expression {
+    Number(Value),
+    Label { name: String, negated: bool },
+}
+
+#[derive(Debug)]
+enum Statement {
+    Instruction {
+        instruction: Instruction,
+        operands: Vec<(ArgumentMode, Expression)>,
+    },
+    Data(Vec<Expression>),
+}
+
+impl Statement {
+    fn size(&self) -> usize {
+        match self {
+            Statement::Instruction { operands, .. } => 1 + operands.len(),
+            Statement::Data(values) => values.len(),
+        }
+    }
+}
+
+fn is_label(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    match chars.next() {
+        Some(first) if first.is_ascii_alphabetic() || first == '_' => {
+            chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        _ => false,
+    }
+}
+
+fn parse_expression(text: &str) -> Option<Expression> {
+    let text = text.trim();
+    let (negated, name) = match text.strip_prefix('-') {
+        Some(name) => (true, name),
+        None => (false, text.strip_prefix('+').unwrap_or(text)),
+    };
+
+    if let Ok(number) = text.parse::<Value>() {
+        Some(Expression::Number(number))
+    } else if is_label(name) {
+        Some(Expression::Label {
+            name: name.to_string(),
+            negated,
+        })
+    } else {
+        None
+    }
+}
+
+fn parse_operand(text: &str) -> Option<(ArgumentMode, Expression)> {
+    if let Some(value) = text.strip_prefix('#') {
+        Some((ArgumentMode::Immediate, parse_expression(value)?))
+    } else if let Some(offset) = text.strip_prefix("rb") {
+        let offset = if offset.is_empty() { "0" } else { offset };
+
+        // The sign is mandatory, so that 'rb5' is not mistaken for 'rb+5'
+        if !offset.starts_with(['+', '-']) && offset != "0" {
+            return None;
+        }
+
+        Some((ArgumentMode::Relative, parse_expression(offset)?))
+    } else {
+        let address = text.strip_prefix('[')?.strip_suffix(']')?;
+        Some((ArgumentMode::Indexed, parse_expression(address)?))
+    }
+}
+
+fn split_list(text: &str) -> Vec<&str> {
+    if text.trim().is_empty() {
+        vec![]
+    } else {
+        text.split(',').map(str::trim).collect()
+    }
+}
+
+fn parse_statement<M: Memory>(
+    text: &str,
+    registry: Option<&Registry<M>>,
+) -> Result<Statement, AssemblyErrorKind> {
+    let (mnemonic, rest) = match text.find(char::is_whitespace) {
+        Some(index) => text.split_at(index),
+        None => (text, ""),
+    };
+
+    if mnemonic == "data" {
+        let values = split_list(rest)
+            .into_iter()
+            .map(|value| {
+                parse_expression(value)
+                    .ok_or_else(|| AssemblyErrorKind::InvalidOperand(value.to_string()))
+            })
+            .collect::<Result<_, _>>()?;
+
+        return Ok(Statement::Data(values));
+    }
+
+    let (instruction, argument_types) = match Instruction::from_mnemonic(mnemonic) {
+        Some(instruction) => (instruction, instruction.argument_types()),
+        None => registry
+            .and_then(|registry| {
+                let opcode = registry.from_mnemonic(mnemonic)?;
+                Some((
+                    Instruction::Extension(opcode),
+                    registry.get(opcode)?.argument_types,
+                ))
+            })
+            .ok_or_else(|| AssemblyErrorKind::UnknownMnemonic(mnemonic.to_string()))?,
+    };
+
+    let operands = split_list(rest);
+    if operands.len() != argument_types.len() {
+        return Err(AssemblyErrorKind::WrongArgumentCount {
+            expected: argument_types.len(),
+            found: operands.len(),
+        });
+    }
+
+    let operands = operands
+        .into_iter()
+        .zip(argument_types)
+        .enumerate()
+        .map(|(index, (text, argument_type))| {
+            let (mode, expression) = parse_operand(text)
+                .ok_or_else(|| AssemblyErrorKind::InvalidOperand(text.to_string()))?;
+
+            if *argument_type == ArgumentType::Out && mode == ArgumentMode::Immediate {
+                return Err(AssemblyErrorKind::ImmediateWrite { argument: index });
+            }
+
+            Ok((mode, expression))
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok(Statement::Instruction {
+        instruction,
+        operands,
+    })
+}
+
+pub fn assemble(source: &str) -> Result<Vec<Value>, AssemblyError> {
+    assemble_source::<PagedMemory>(source, None)
+}
+
+// Same as assemble, but also accepting the mnemonics of the extensions in a registry
+pub fn assemble_with<M: Memory>(
+    source: &str,
+    registry: &Registry<M>,
+) -> Result<Vec<Value>, AssemblyError> {
+    assemble_source(source, Some(registry))
+}
+
+fn assemble_source<M: Memory>(
+    source: &str,
+    registry: Option<&Registry<M>>,
+) -> Result<Vec<Value>, AssemblyError> {
+    let mut labels = HashMap::<String, Address>::new();
+    let mut statements = Vec::new();
+    let mut address = 0;
+
+    // The first pass parses all statements and figures out where labels point to
+    for (index, line) in source.lines().enumerate() {
+        let line_number = index + 1;
+        let error = |kind| AssemblyError {
+            line: line_number,
+            kind,
+        };
+
+        let mut text = line.split(';').next().unwrap().trim();
+
+        if let Some(colon) = text.find(':') {
+            let label = text[..colon].trim();
+
+            if let Ok(expected) = label.parse::<Address>() {
+                if expected != address {
+                    return Err(error(AssemblyErrorKind::AddressMismatch {
+                        expected,
+                        found: address,
+                    }));
+                }
+            } else if !is_label(label) {
+                return Err(error(AssemblyErrorKind::InvalidLabel(label.to_string())));
+            } else if labels.insert(label.to_string(), address).is_some() {
+                return Err(error(AssemblyErrorKind::DuplicateLabel(label.to_string())));
+            }
+
+            text = text[colon + 1..].trim();
+        }
+
+        if !text.is_empty() {
+            let statement = parse_statement(text, registry).map_err(error)?;
+            address += statement.size();
+            statements.push((line_number, statement));
+        }
+    }
+
+    // The second pass emits the program, now that all labels can be resolved
+    let mut program = Vec::with_capacity(address);
+
+    for (line_number, statement) in statements {
+        let resolve = |expression: &Expression| match expression {
+            Expression::Number(number) => Ok(*number),
+            Expression::Label { name, negated } => {
+                let address = labels.get(name).ok_or_else(|| AssemblyError {
+                    line: line_number,
+                    kind: AssemblyErrorKind::UndefinedLabel(name.to_string()),
+                })?;
+                let value = Value::try_from(*address).unwrap();
+
+                Ok(if *negated { -value } else { value })
+            }
+        };
+
+        match statement {
+            Statement::Instruction {
+                instruction,
+                operands,
+            } => {
+                let modes: Vec<_> = operands.iter().map(|(mode, _)| *mode).collect();
+                program.push(instruction.encode(&modes));
+
+                for (_, expression) in &operands {
+                    program.push(resolve(expression)?);
+                }
+            }
+            Statement::Data(values) => {
+                for expression in &values {
+                    program.push(resolve(expression)?);
+                }
+            }
+        }
+    }
+
+    Ok(program)
+}
+
+#[cfg(test)]
+mod assembly {
+    use super::*;
+    use crate::disassembler;
+
+    #[test]
+    fn operand_modes() {
+        assert_eq!(
+            assemble("add #5, [12], rb+3\nout rb-1\narb #-4\nhlt"),
+            Ok(vec![20101, 5, 12, 3, 204, -1, 109, -4, 99])
+        );
+    }
+
+    #[test]
+    fn labels_and_data() {
+        // Test if input is less than 8
+        let source = "
+            in   [input]
+            lt   [input], [eight], [input]  ; overwrite the input with the result
+            out  [input]
+            hlt
+            input: data -1
+            eight: data 8
+        ";
+
+        assert_eq!(
+            assemble(source),
+            Ok(vec![3, 9, 7, 9, 10, 9, 4, 9, 99, -1, 8])
+        );
+    }
+
+    #[test]
+    fn forward_and_backward_jumps() {
+        let source = "
+            start: jt  #1, #end
+                   jf  #0, #start
+            end:   data start, end, -end
+        ";
+
+        assert_eq!(assemble(source), Ok(vec![1105, 1, 6, 1106, 0, 0, 0, 6, -6]));
+    }
+
+    #[test]
+    fn round_trip() {
+        let program = vec![
+            3, 21, 1008, 21, 8, 20, 1005, 20, 22, 107, 8, 21, 20, 1006, 20, 31, 1106, 0, 36, 98, 0,
+            0, 1002, 21, 125, 20, 4, 20, 1105, 1, 46, 104, 999, 1105, 1, 46, 1101, 1000, 1, 20, 4,
+            20, 1105, 1, 46, 98, 99,
+        ];
+
+        assert_eq!(assemble(&disassembler::listing(&program)), Ok(program));
+    }
+
+    #[test]
+    fn extensions() {
+        let mut registry: Registry = Registry::new();
+        registry
+            .register(
+                42,
+                crate::registry::Extension {
+                    mnemonic: "swap",
+                    argument_types: &[ArgumentType::Out, ArgumentType::Out],
+                    exec: |_, _| None,
+                },
+            )
+            .unwrap();
+
+        let source = "swap [1], rb+2\nhlt";
+        assert_eq!(
+            assemble(source),
+            Err(AssemblyError {
+                line: 1,
+                kind: AssemblyErrorKind::UnknownMnemonic("swap".to_string())
+            })
+        );
+        assert_eq!(assemble_with(source, &registry), Ok(vec![2042, 1, 2, 99]));
+        assert_eq!(
+            assemble_with("swap [1]", &registry),
+            Err(AssemblyError {
+                line: 1,
+                kind: AssemblyErrorKind::WrongArgumentCount {
+                    expected: 2,
+                    found: 1
+                }
+            })
+        );
+    }
+
+    fn assert_error(source: &str, line: usize, kind: AssemblyErrorKind) {
+        assert_eq!(assemble(source), Err(AssemblyError { line, kind }));
+    }
+
+    #[test]
+    fn errors() {
+        use AssemblyErrorKind::*;
+
+        assert_error("hlt\nnop", 2, UnknownMnemonic("nop".to_string()));
+        assert_error(
+            "add #1, #2",
+            1,
+            WrongArgumentCount {
+                expected: 3,
+                found: 2,
+            },
+        );
+        assert_error("out 5", 1, InvalidOperand("5".to_string()));
+        assert_error("out rb5", 1, InvalidOperand("rb5".to_string()));
+        assert_error("in #5", 1, ImmediateWrite { argument: 0 });
+        assert_error("1st: hlt", 1, InvalidLabel("1st".to_string()));
+        assert_error("a: hlt\n\na: hlt", 3, DuplicateLabel("a".to_string()));
+        assert_error(
+            "hlt\njt #1, #nowhere",
+            2,
+            UndefinedLabel("nowhere".to_string()),
+        );
+        assert_error(
+            "hlt\n3: hlt",
+            2,
+            AddressMismatch {
+                expected: 3,
+                found: 1,
+            },
+        );
+    }
+}
@@ -0,0 +1,32 @@
+use std::env;
+use std::fs;
+
+use intcode::analysis;
+
+fn main() {
+    let args: Vec<_> = env::args().skip(1).collect();
+    let args: Vec<_> = args.iter().map(String::as_str).collect();
+
+    let (filename, dot) = match args[..] {
+        [] => ("input", false),
+        ["--dot"] => ("input", true),
+        [filename] => (filename, false),
+        ["--dot", filename] => (filename, true),
+        _ => panic!("Usage: analyze [--dot] <file>"),
+    };
+
+    let contents = fs::read_to_string(filename)
+        .unwrap_or_else(|_| panic!("Failed to read from file '{}'", filename));
+
+    let program = intcode::parse_program(&contents)
+        .unwrap_or_else(|error| panic!("Failed to parse program: {}", error));
+
+    let analysis = analysis::analyze(&program);
+
+    // The graph is meant to be piped into Graphviz, e.g. 'dot -Tsvg'
+    if dot {
+        print!("{}", analysis.to_dot());
+    } else {
+        print!("{}", analysis.report());
+    }
+}
@@ -0,0 +1,18 @@
+use std::env;
+use std::fs;
+
+use intcode::assembler;
+
+fn main() {
+    let filename = env::args()
+        .nth(1)
+        .unwrap_or_else(|| panic!("Usage: assemble <file>"));
+    let contents = fs::read_to_string(&filename)
+        .unwrap_or_else(|_| panic!("Failed to read from file '{}'", filename));
+
+    let program = assembler::assemble(&contents)
+        .unwrap_or_else(|error| panic!("Failed to assemble program: {}", error));
+
+    let words: Vec<_> = program.iter().map(|value| value.to_string()).collect();
+    println!("{}", words.join(","));
+}
@@ -0,0 +1,32 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::iter;
+
+use intcode::debugger::Debugger;
+use intcode::Computer;
+
+fn main() {
+    let filename = env::args()
+        .nth(1)
+        .unwrap_or_else(|| panic!("Usage: debug <file>"));
+    let contents = fs::read_to_string(&filename)
+        .unwrap_or_else(|_| panic!("Failed to read from file '{}'", filename));
+
+    let program = intcode::parse_program(&contents)
+        .unwrap_or_else(|error| panic!("Failed to parse program: {}", error));
+
+    // Commands are read one line at a time, so that the console can also read program input
+    let commands = iter::from_fn(|| {
+        let mut line = String::new();
+        match io::stdin().read_line(&mut line) {
+            Ok(0) | Err(_) => None,
+            Ok(_) => Some(line),
+        }
+    });
+
+    let mut computer = Computer::new(program, None);
+    Debugger::new(&mut computer)
+        .repl(commands, io::stdout())
+        .unwrap();
+}
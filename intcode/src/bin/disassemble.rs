@@ -0,0 +1,15 @@
+use std::env;
+use std::fs;
+
+use intcode::disassembler;
+
+fn main() {
+    let filename = env::args().nth(1).unwrap_or_else(|| "input".to_string());
+    let contents = fs::read_to_string(&filename)
+        .unwrap_or_else(|_| panic!("Failed to read from file '{}'", filename));
+
+    let program = intcode::parse_program(&contents)
+        .unwrap_or_else(|error| panic!("Failed to parse program: {}", error));
+
+    print!("{}", disassembler::listing(&program));
+}
@@ -0,0 +1,44 @@
+use std::env;
+use std::fs;
+
+use intcode::profiler::Profiler;
+use intcode::Computer;
+
+// Number of addresses listed in the report
+const HOT_SPOTS: usize = 20;
+
+fn main() {
+    let args: Vec<_> = env::args().skip(1).collect();
+    let args: Vec<_> = args.iter().map(String::as_str).collect();
+
+    let (filename, annotate) = match args[..] {
+        [filename] => (filename, false),
+        ["--annotate", filename] => (filename, true),
+        _ => panic!("Usage: profile [--annotate] <file>"),
+    };
+
+    let contents = fs::read_to_string(filename)
+        .unwrap_or_else(|_| panic!("Failed to read from file '{}'", filename));
+
+    let program = intcode::parse_program(&contents)
+        .unwrap_or_else(|error| panic!("Failed to parse program: {}", error));
+
+    // The program runs on the console, so the report is written to stderr to keep it apart from
+    // the program's output
+    let mut profiler = Profiler::new();
+    let result = {
+        let mut computer = Computer::new(program.clone(), None);
+        computer.set_profiler(&mut profiler);
+        computer.run()
+    };
+
+    if let Err(error) = result {
+        eprintln!("Program failed: {}", error);
+    }
+
+    eprint!("{}", profiler.report(HOT_SPOTS));
+
+    if annotate {
+        eprint!("\n{}", profiler.annotate(&program));
+    }
+}
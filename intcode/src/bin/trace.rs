@@ -0,0 +1,67 @@
+use std::env;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter};
+
+use intcode::trace::{self, Step, TraceWriter};
+use intcode::{Computer, Value};
+
+fn read_program(filename: &str) -> Vec<Value> {
+    let contents = fs::read_to_string(filename)
+        .unwrap_or_else(|_| panic!("Failed to read from file '{}'", filename));
+
+    intcode::parse_program(&contents)
+        .unwrap_or_else(|error| panic!("Failed to parse program: {}", error))
+}
+
+fn read_trace(filename: &str) -> Vec<Step> {
+    let file =
+        File::open(filename).unwrap_or_else(|_| panic!("Failed to read from file '{}'", filename));
+
+    trace::read_trace(BufReader::new(file))
+        .unwrap_or_else(|error| panic!("Failed to parse trace: {}", error))
+}
+
+fn print_diff(left: &[Step], right: &[Step]) {
+    match trace::diff(left, right) {
+        None => println!("Traces are identical ({} steps)", left.len()),
+        Some(divergence) => {
+            println!("Traces diverge at step {}", divergence.index);
+            println!("< {:?}", divergence.left);
+            println!("> {:?}", divergence.right);
+        }
+    }
+}
+
+fn main() {
+    let args: Vec<_> = env::args().skip(1).collect();
+    let args: Vec<_> = args.iter().map(String::as_str).collect();
+
+    match args[..] {
+        // Runs a program on the console, recording its trace
+        ["record", program, trace_file] => {
+            let file = File::create(trace_file)
+                .unwrap_or_else(|_| panic!("Failed to create file '{}'", trace_file));
+            let mut writer = TraceWriter::new(BufWriter::new(file));
+
+            let mut computer = Computer::new(read_program(program), None);
+            computer.set_tracer(&mut writer);
+            let result = computer.run();
+
+            writer.finish().unwrap();
+            result.unwrap_or_else(|error| panic!("Program failed: {}", error));
+        }
+        // Runs a program with the inputs of a trace, comparing the new trace with it
+        ["replay", program, trace_file] => {
+            let recorded = read_trace(trace_file);
+            let (replayed, result) = trace::replay(read_program(program), &recorded);
+
+            if let Err(error) = result {
+                println!("Program failed: {}", error);
+            }
+
+            print_diff(&recorded, &replayed);
+        }
+        ["diff", left, right] => print_diff(&read_trace(left), &read_trace(right)),
+        _ => panic!("Usage: trace record <program> <trace> | replay <program> <trace> | diff <trace> <trace>"),
+    }
+}
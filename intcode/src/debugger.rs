@@ -0,0 +1,413 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{self, Write};
+
+use super::disassembler;
+use super::*;
+
+// Number of memory cells shown on each row of the memory view
+const ROW_LENGTH: usize = 8;
+
+// Reasons for which the debugger hands control back to the user
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stop {
+    Stepped,
+    Breakpoint(Address),
+    Watchpoint {
+        address: Address,
+        old_value: Value,
+        new_value: Value,
+    },
+    Halted,
+    Error(IntcodeError),
+}
+
+pub struct Debugger<'c, 'hw, M = PagedMemory> {
+    computer: &'c mut Computer<'hw, M>,
+    breakpoints: BTreeSet<Address>,
+    // Watched memory cells, along with their last known value
+    watchpoints: BTreeMap<Address, Value>,
+    halted: bool,
+    // Where control was last handed back to the user. A breakpoint there has already been hit.
+    stopped_at: Option<Address>,
+}
+
+impl<'c, 'hw, M: Memory> Debugger<'c, 'hw, M> {
+    pub fn new(computer: &'c mut Computer<'hw, M>) -> Debugger<'c, 'hw, M> {
+        Debugger {
+            computer,
+            breakpoints: BTreeSet::new(),
+            watchpoints: BTreeMap::new(),
+            halted: false,
+            stopped_at: None,
+        }
+    }
+
+    pub fn add_breakpoint(&mut self, address: Address) {
+        self.breakpoints.insert(address);
+    }
+
+    pub fn remove_breakpoint(&mut self, address: Address) {
+        self.breakpoints.remove(&address);
+    }
+
+    pub fn add_watchpoint(&mut self, address: Address) {
+        let value = self.computer.hardware.read(address);
+        self.watchpoints.insert(address, value);
+    }
+
+    pub fn remove_watchpoint(&mut self, address: Address) {
+        self.watchpoints.remove(&address);
+    }
+
+    // Executes a single instruction (servicing IO if it is an input or output one)
+    pub fn step(&mut self) -> Stop {
+        if self.halted {
+            return Stop::Halted;
+        }
+
+        let stop = match self.computer.step_with_io() {
+            Err(error) => Stop::Error(error),
+            Ok(Instruction::Halt) => {
+                self.halted = true;
+                Stop::Halted
+            }
+            Ok(_) => self.check_watchpoints().unwrap_or(Stop::Stepped),
+        };
+
+        self.stopped_at = Some(self.computer.program_counter());
+        stop
+    }
+
+    // Executes instructions until a breakpoint or watchpoint is hit, or the program stops running.
+    // Breakpoints stop execution before their instruction runs, so one at the current address is hit
+    // right away unless execution already stopped there.
+    pub fn resume(&mut self) -> Stop {
+        let program_counter = self.computer.program_counter();
+        if self.breakpoints.contains(&program_counter) && self.stopped_at != Some(program_counter) {
+            self.stopped_at = Some(program_counter);
+            return Stop::Breakpoint(program_counter);
+        }
+
+        loop {
+            match self.step() {
+                Stop::Stepped => {
+                    let program_counter = self.computer.program_counter();
+
+                    if self.breakpoints.contains(&program_counter) {
+                        break Stop::Breakpoint(program_counter);
+                    }
+                }
+                stop => break stop,
+            }
+        }
+    }
+
+    // Updates the known values of all watched cells, reporting the first one that changed
+    fn check_watchpoints(&mut self) -> Option<Stop> {
+        let mut stop = None;
+
+        for (address, old_value) in self.watchpoints.iter_mut() {
+            let new_value = self.computer.hardware.read(*address);
+
+            if new_value != *old_value && stop.is_none() {
+                stop = Some(Stop::Watchpoint {
+                    address: *address,
+                    old_value: *old_value,
+                    new_value,
+                });
+            }
+
+            *old_value = new_value;
+        }
+
+        stop
+    }
+
+    // Registers, the instruction about to be executed and its arguments, and the memory around it
+    pub fn describe(&self) -> String {
+        let program_counter = self.computer.program_counter();
+        let mut description = format!(
+            "pc: {}, rb: {}\n",
+            program_counter,
+            self.computer.relative_base()
+        );
+
+        let words: Vec<_> = (program_counter..program_counter + 4)
+            .map(|address| self.computer.hardware.read(address))
+            .collect();
+
+        let item = match self.computer.registry {
+            Some(registry) => disassembler::decode_with(&words, 0, registry),
+            None => disassembler::decode(&words, 0),
+        };
+
+        match item {
+            Some(disassembler::Item::Instruction {
+                instruction,
+                mnemonic,
+                operands,
+                ..
+            }) => {
+                let item = disassembler::Item::Instruction {
+                    address: program_counter,
+                    instruction,
+                    mnemonic,
+                    operands,
+                };
+                description += &format!("{}\n", item);
+
+                let decoded = Decoded::new(words[0])
+                    .ok()
+                    .or_else(|| self.computer.registry?.decode(words[0]))
+                    .unwrap();
+
+                if let Ok(arguments) = self.computer.arguments(&decoded) {
+                    let arguments: Vec<_> = arguments
+                        .iter()
+                        .map(|argument| match argument {
+                            Argument::In(value) => value.to_string(),
+                            Argument::Out(address) => format!("-> {}", address),
+                        })
+                        .collect();
+
+                    description += &format!("        arguments: {}\n", arguments.join(", "));
+                }
+            }
+            _ => {
+                description += &format!("{:>6}: data {}\n", program_counter, words[0]);
+            }
+        }
+
+        description + &self.memory_view(program_counter)
+    }
+
+    // Rows of memory cells surrounding an address, which is marked with '>'
+    fn memory_view(&self, center: Address) -> String {
+        let first_row = (center / ROW_LENGTH).saturating_sub(1) * ROW_LENGTH;
+
+        (first_row..first_row + 3 * ROW_LENGTH)
+            .step_by(ROW_LENGTH)
+            .map(|row| {
+                let cells: Vec<_> = (row..row + ROW_LENGTH)
+                    .map(|address| {
+                        let marker = if address == center { ">" } else { "" };
+                        let cell = format!("{}{}", marker, self.computer.hardware.read(address));
+                        format!("{:>7}", cell)
+                    })
+                    .collect();
+
+                format!("{:>6}:{}\n", row, cells.join(""))
+            })
+            .collect()
+    }
+
+    fn report(&self, stop: &Stop) -> String {
+        let reason = match stop {
+            Stop::Stepped => String::new(),
+            Stop::Breakpoint(address) => format!("Breakpoint at {}\n", address),
+            Stop::Watchpoint {
+                address,
+                old_value,
+                new_value,
+            } => format!(
+                "Watchpoint at {}: {} -> {}\n",
+                address, old_value, new_value
+            ),
+            Stop::Halted => "Halted\n".to_string(),
+            Stop::Error(error) => format!("Error: {}\n", error),
+        };
+
+        reason + &self.describe()
+    }
+
+    // Reads commands until they run out or the user quits, printing the state of the computer
+    // each time execution stops. Commands are taken one line at a time (instead of from a BufRead)
+    // so that the program being debugged can also read from the same source.
+    pub fn repl<I, W>(&mut self, commands: I, mut output: W) -> io::Result<()>
+    where
+        I: IntoIterator<Item = String>,
+        W: Write,
+    {
+        write!(output, "{}", self.describe())?;
+
+        for line in commands {
+            let words: Vec<_> = line.split_whitespace().collect();
+
+            let address = words.get(1).and_then(|word| word.parse::<Address>().ok());
+
+            match (words.first().copied(), address) {
+                (None, _) | (Some("s"), _) | (Some("step"), _) => {
+                    let stop = self.step();
+                    write!(output, "{}", self.report(&stop))?;
+                }
+                (Some("c"), _) | (Some("continue"), _) => {
+                    let stop = self.resume();
+                    write!(output, "{}", self.report(&stop))?;
+                }
+                (Some("b"), Some(address)) | (Some("break"), Some(address)) => {
+                    self.add_breakpoint(address);
+                }
+                (Some("d"), Some(address)) | (Some("delete"), Some(address)) => {
+                    self.remove_breakpoint(address);
+                }
+                (Some("w"), Some(address)) | (Some("watch"), Some(address)) => {
+                    self.add_watchpoint(address);
+                }
+                (Some("u"), Some(address)) | (Some("unwatch"), Some(address)) => {
+                    self.remove_watchpoint(address);
+                }
+                (Some("p"), Some(address)) | (Some("print"), Some(address)) => {
+                    writeln!(output, "{}", self.computer.hardware.read(address))?;
+                }
+                (Some("q"), _) | (Some("quit"), _) => break,
+                _ => writeln!(
+                    output,
+                    "Commands: (s)tep, (c)ontinue, (b)reak <address>, (d)elete <address>, \
+                     (w)atch <address>, (u)nwatch <address>, (p)rint <address>, (q)uit"
+                )?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod debugging {
+    use super::*;
+
+    // Counts down from 3, storing the counter at address 10
+    fn countdown() -> Vec<Value> {
+        assembler::assemble(
+            "
+            loop: out [n]
+                  add [n], #-1, [n]
+                  jt [n], #loop
+                  hlt
+            n:    data 3
+            ",
+        )
+        .unwrap()
+    }
+
+    struct NullIO;
+
+    impl IO for NullIO {
+        fn input(&mut self) -> Option<Value> {
+            None
+        }
+
+        fn output(&mut self, _: Value) {}
+    }
+
+    #[test]
+    fn breakpoints() {
+        let mut io = NullIO;
+        let mut computer = Computer::new(countdown(), Some(&mut io));
+        let mut debugger = Debugger::new(&mut computer);
+
+        debugger.add_breakpoint(2);
+        assert_eq!(debugger.resume(), Stop::Breakpoint(2));
+        assert_eq!(debugger.resume(), Stop::Breakpoint(2));
+
+        debugger.remove_breakpoint(2);
+        assert_eq!(debugger.resume(), Stop::Halted);
+        assert_eq!(debugger.step(), Stop::Halted);
+    }
+
+    #[test]
+    fn breakpoint_at_entry() {
+        let mut io = NullIO;
+        let mut computer = Computer::new(countdown(), Some(&mut io));
+        let mut debugger = Debugger::new(&mut computer);
+
+        // The loop starts at 0, so it is hit before anything runs and then once per iteration
+        debugger.add_breakpoint(0);
+        assert_eq!(debugger.resume(), Stop::Breakpoint(0));
+        assert_eq!(debugger.resume(), Stop::Breakpoint(0));
+        assert_eq!(debugger.resume(), Stop::Breakpoint(0));
+        assert_eq!(debugger.resume(), Stop::Halted);
+
+        // Stepping onto a breakpoint hands control back, so resuming goes past it
+        let mut computer = Computer::new(countdown(), Some(&mut io));
+        let mut debugger = Debugger::new(&mut computer);
+        debugger.add_breakpoint(2);
+        assert_eq!(debugger.step(), Stop::Stepped);
+        assert_eq!(debugger.resume(), Stop::Breakpoint(2));
+        assert_eq!(debugger.computer.hardware.read(10), 2);
+    }
+
+    #[test]
+    fn watchpoints() {
+        let mut io = NullIO;
+        let mut computer = Computer::new(countdown(), Some(&mut io));
+        let mut debugger = Debugger::new(&mut computer);
+
+        debugger.add_watchpoint(10);
+        assert_eq!(debugger.step(), Stop::Stepped);
+        assert_eq!(
+            debugger.resume(),
+            Stop::Watchpoint {
+                address: 10,
+                old_value: 3,
+                new_value: 2
+            }
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let mut io = NullIO;
+        let mut computer = Computer::new(vec![3, 0, 99], Some(&mut io));
+        let mut debugger = Debugger::new(&mut computer);
+
+        assert_eq!(
+            debugger.resume(),
+            Stop::Error(IntcodeError::InputExhausted {
+                program_counter: 0,
+                instruction: 3
+            })
+        );
+    }
+
+    #[test]
+    fn repl() {
+        let mut io = NullIO;
+        let mut computer = Computer::new(countdown(), Some(&mut io));
+        let mut debugger = Debugger::new(&mut computer);
+
+        let mut output = Vec::new();
+        debugger
+            .repl(
+                "s\nb 6\nc\np 10\nq\ns".lines().map(String::from),
+                &mut output,
+            )
+            .unwrap();
+
+        let expected = [
+            "pc: 0, rb: 0",
+            "     0: out  [10]",
+            "        arguments: 3",
+            "     0:     >4     10   1001     10     -1     10   1005     10",
+            "     8:      0     99      3      0      0      0      0      0",
+            "    16:      0      0      0      0      0      0      0      0",
+            "pc: 2, rb: 0",
+            "     2: add  [10], #-1, [10]",
+            "        arguments: 3, -1, -> 10",
+            "     0:      4     10  >1001     10     -1     10   1005     10",
+            "     8:      0     99      3      0      0      0      0      0",
+            "    16:      0      0      0      0      0      0      0      0",
+            "Breakpoint at 6",
+            "pc: 6, rb: 0",
+            "     6: jt   [10], #0",
+            "        arguments: 2, 0",
+            "     0:      4     10   1001     10     -1     10  >1005     10",
+            "     8:      0     99      2      0      0      0      0      0",
+            "    16:      0      0      0      0      0      0      0      0",
+            "2",
+            "",
+        ];
+
+        assert_eq!(String::from_utf8(output).unwrap(), expected.join("\n"));
+    }
+}
@@ -0,0 +1,300 @@
+use core::convert::TryFrom;
+use std::fmt;
+
+use super::hardware::*;
+use super::instruction::*;
+use super::memory::{Memory, PagedMemory};
+use super::registry::Registry;
+
+// An argument as written in a program, along with how it is to be interpreted
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Operand {
+    pub mode: ArgumentMode,
+    pub value: Value,
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.mode {
+            ArgumentMode::Immediate => write!(f, "#{}", self.value),
+            ArgumentMode::Indexed => write!(f, "[{}]", self.value),
+            ArgumentMode::Relative => write!(f, "rb{:+}", self.value),
+        }
+    }
+}
+
+// Each entry in a listing is either a decoded instruction (spanning multiple words) or a
+// single word that could not be decoded
+#[derive(Debug, Clone, PartialEq)]
+pub enum Item {
+    Instruction {
+        address: Address,
+        instruction: Instruction,
+        // Extensions are named by the registry they were decoded with
+        mnemonic: &'static str,
+        operands: Vec<Operand>,
+    },
+    Data {
+        address: Address,
+        value: Value,
+    },
+}
+
+impl Item {
+    pub fn address(&self) -> Address {
+        match self {
+            Item::Instruction { address, .. } | Item::Data { address, .. } => *address,
+        }
+    }
+
+    // Number of words in the program this item takes up
+    pub fn size(&self) -> usize {
+        match self {
+            Item::Instruction { operands, .. } => 1 + operands.len(),
+            Item::Data { .. } => 1,
+        }
+    }
+}
+
+impl fmt::Display for Item {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (address, mnemonic, operands) = match self {
+            Item::Instruction {
+                address,
+                mnemonic,
+                operands,
+                ..
+            } => (
+                address,
+                *mnemonic,
+                operands.iter().map(Operand::to_string).collect(),
+            ),
+            Item::Data { address, value } => (address, "data", vec![value.to_string()]),
+        };
+
+        let line = format!("{:>6}: {:<4} {}", address, mnemonic, operands.join(", "));
+        write!(f, "{}", line.trim_end())
+    }
+}
+
+// Attempts to decode the instruction at an address. This fails if the opcode or any of the modes
+// are unknown, if there are leftover mode indicators, if an out-argument is immediate or if the
+// program ends before all arguments do.
+pub fn decode(program: &[Value], address: Address) -> Option<Item> {
+    decode_item::<PagedMemory>(program, address, None)
+}
+
+// Same as decode, but also decoding the extensions in a registry
+pub fn decode_with<M: Memory>(
+    program: &[Value],
+    address: Address,
+    registry: &Registry<M>,
+) -> Option<Item> {
+    decode_item(program, address, Some(registry))
+}
+
+fn decode_item<M: Memory>(
+    program: &[Value],
+    address: Address,
+    registry: Option<&Registry<M>>,
+) -> Option<Item> {
+    let value = *program.get(address)?;
+    let (instruction, mnemonic, argument_types) = match Instruction::try_from(value) {
+        Ok(instruction) => (
+            instruction,
+            instruction.mnemonic(),
+            instruction.argument_types(),
+        ),
+        Err(_) => {
+            let opcode = value % 100;
+            let extension = registry?.get(opcode)?;
+
+            (
+                Instruction::Extension(opcode),
+                extension.mnemonic,
+                extension.argument_types,
+            )
+        }
+    };
+
+    let unused_modes = value / 100 / 10_i64.pow(u32::try_from(argument_types.len()).unwrap());
+    if unused_modes != 0 {
+        return None;
+    }
+
+    let operands = argument_types
+        .iter()
+        .enumerate()
+        .map(|(index, argument_type)| {
+            let mode = ArgumentMode::of_argument(value, index).ok()?;
+            let operand_value = *program.get(address + 1 + index)?;
+
+            match (argument_type, mode) {
+                (ArgumentType::Out, ArgumentMode::Immediate) => None,
+                (_, ArgumentMode::Indexed) if operand_value < 0 => None,
+                _ => Some(Operand {
+                    mode,
+                    value: operand_value,
+                }),
+            }
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    Some(Item::Instruction {
+        address,
+        instruction,
+        mnemonic,
+        operands,
+    })
+}
+
+// Splits a program into items, decoding instructions linearly: words that do not decode are
+// treated as data, and decoding resumes on the word right after them
+pub fn disassemble(program: &[Value]) -> Vec<Item> {
+    disassemble_items::<PagedMemory>(program, None)
+}
+
+pub fn disassemble_with<M: Memory>(program: &[Value], registry: &Registry<M>) -> Vec<Item> {
+    disassemble_items(program, Some(registry))
+}
+
+fn disassemble_items<M: Memory>(program: &[Value], registry: Option<&Registry<M>>) -> Vec<Item> {
+    let mut items = Vec::new();
+    let mut address = 0;
+
+    while address < program.len() {
+        let item = decode_item(program, address, registry).unwrap_or(Item::Data {
+            address,
+            value: program[address],
+        });
+
+        address += item.size();
+        items.push(item);
+    }
+
+    items
+}
+
+// Human readable listing of a program, one item per line
+pub fn listing(program: &[Value]) -> String {
+    lines(&disassemble(program))
+}
+
+pub fn listing_with<M: Memory>(program: &[Value], registry: &Registry<M>) -> String {
+    lines(&disassemble_with(program, registry))
+}
+
+fn lines(items: &[Item]) -> String {
+    items.iter().map(|item| format!("{}\n", item)).collect()
+}
+
+#[cfg(test)]
+mod disassembly {
+    use super::*;
+
+    #[test]
+    fn operand_modes() {
+        assert_eq!(
+            listing(&[21101, 5, 12, 3, 99]),
+            "     0: add  #5, #12, rb+3\n     4: hlt\n"
+        );
+    }
+
+    #[test]
+    fn all_mnemonics() {
+        let program = vec![
+            1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 4, 0, 5, 0, 0, 6, 0, 0, 7, 0, 0, 0, 8, 0, 0, 0, 9, 0, 99,
+        ];
+
+        let mnemonics: Vec<_> = disassemble(&program)
+            .iter()
+            .map(|item| match item {
+                Item::Instruction { mnemonic, .. } => *mnemonic,
+                Item::Data { .. } => "data",
+            })
+            .collect();
+
+        assert_eq!(
+            mnemonics,
+            vec!["add", "mul", "in", "out", "jt", "jf", "lt", "eq", "arb", "hlt"]
+        );
+    }
+
+    #[test]
+    fn relative_operands() {
+        assert_eq!(
+            listing(&[109, -1, 204, 2]),
+            "     0: arb  #-1\n     2: out  rb+2\n"
+        );
+    }
+
+    #[test]
+    fn data_words() {
+        let program = vec![104, 5, 42, -1, 11101, 50, 60, 70, 2, 0, 0];
+
+        assert_eq!(
+            listing(&program),
+            [
+                "     0: out  #5",
+                // Unknown opcodes
+                "     2: data 42",
+                "     3: data -1",
+                // Immediate out-argument
+                "     4: data 11101",
+                "     5: data 50",
+                "     6: data 60",
+                "     7: data 70",
+                // Missing arguments
+                "     8: data 2",
+                "     9: data 0",
+                "    10: data 0",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn negative_indexed_operand() {
+        assert_eq!(
+            disassemble(&[4, -2])[0],
+            Item::Data {
+                address: 0,
+                value: 4
+            }
+        );
+    }
+
+    #[test]
+    fn extensions() {
+        let mut registry: Registry = Registry::new();
+        registry
+            .register(
+                42,
+                crate::registry::Extension {
+                    mnemonic: "swap",
+                    argument_types: &[ArgumentType::Out, ArgumentType::Out],
+                    exec: |_, _| None,
+                },
+            )
+            .unwrap();
+
+        let program = vec![2042, 1, 2, 99];
+        assert_eq!(listing(&program).lines().next(), Some("     0: data 2042"));
+        assert_eq!(
+            listing_with(&program, &registry),
+            "     0: swap [1], rb+2\n     3: hlt\n"
+        );
+    }
+
+    #[test]
+    fn leftover_modes() {
+        assert_eq!(
+            disassemble(&[10099])[0],
+            Item::Data {
+                address: 0,
+                value: 10099
+            }
+        );
+    }
+}
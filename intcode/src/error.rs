@@ -0,0 +1,140 @@
+use std::error::Error;
+use std::fmt;
+
+use super::hardware::*;
+
+// Ways in which a program can fail to run. All of them carry the program counter and the raw value
+// of the instruction that was being executed when the failure happened.
+#[derive(Debug, Clone, PartialEq)]
+pub enum IntcodeError {
+    UnknownOpcode {
+        program_counter: Address,
+        instruction: Value,
+    },
+    InvalidMode {
+        program_counter: Address,
+        instruction: Value,
+        argument: usize,
+        mode: Value,
+    },
+    ImmediateWrite {
+        program_counter: Address,
+        instruction: Value,
+        argument: usize,
+    },
+    NegativeAddress {
+        program_counter: Address,
+        instruction: Value,
+        address: Value,
+    },
+    RelativeBaseUnderflow {
+        program_counter: Address,
+        instruction: Value,
+        relative_base: Address,
+        change: Value,
+    },
+    // A relative argument's address is too large to be represented
+    AddressOverflow {
+        program_counter: Address,
+        instruction: Value,
+        relative_base: Address,
+        offset: Value,
+    },
+    InputExhausted {
+        program_counter: Address,
+        instruction: Value,
+    },
+}
+
+impl IntcodeError {
+    pub fn program_counter(&self) -> Address {
+        use IntcodeError::*;
+
+        match self {
+            UnknownOpcode {
+                program_counter, ..
+            }
+            | InvalidMode {
+                program_counter, ..
+            }
+            | ImmediateWrite {
+                program_counter, ..
+            }
+            | NegativeAddress {
+                program_counter, ..
+            }
+            | RelativeBaseUnderflow {
+                program_counter, ..
+            }
+            | AddressOverflow {
+                program_counter, ..
+            }
+            | InputExhausted {
+                program_counter, ..
+            } => *program_counter,
+        }
+    }
+
+    pub fn instruction(&self) -> Value {
+        use IntcodeError::*;
+
+        match self {
+            UnknownOpcode { instruction, .. }
+            | InvalidMode { instruction, .. }
+            | ImmediateWrite { instruction, .. }
+            | NegativeAddress { instruction, .. }
+            | RelativeBaseUnderflow { instruction, .. }
+            | AddressOverflow { instruction, .. }
+            | InputExhausted { instruction, .. } => *instruction,
+        }
+    }
+}
+
+impl fmt::Display for IntcodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use IntcodeError::*;
+
+        match self {
+            UnknownOpcode { .. } => write!(f, "unknown opcode"),
+            InvalidMode { argument, mode, .. } => {
+                write!(f, "invalid mode {} for argument {}", mode, argument)
+            }
+            ImmediateWrite { argument, .. } => {
+                write!(
+                    f,
+                    "argument {} is written to but is in immediate mode",
+                    argument
+                )
+            }
+            NegativeAddress { address, .. } => write!(f, "negative address {}", address),
+            RelativeBaseUnderflow {
+                relative_base,
+                change,
+                ..
+            } => write!(
+                f,
+                "relative base {} cannot be moved by {}",
+                relative_base, change
+            ),
+            AddressOverflow {
+                relative_base,
+                offset,
+                ..
+            } => write!(
+                f,
+                "address of relative base {} plus {} overflows",
+                relative_base, offset
+            ),
+            InputExhausted { .. } => write!(f, "no input available"),
+        }?;
+
+        write!(
+            f,
+            " (instruction {} at address {})",
+            self.instruction(),
+            self.program_counter()
+        )
+    }
+}
+
+impl Error for IntcodeError {}
@@ -0,0 +1,78 @@
+use core::convert::TryInto;
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use super::memory::{Memory, PagedMemory};
+
+pub type Address = usize;
+pub type Value = i64;
+
+pub trait AddressLike {
+    fn from_value(value: Value) -> Result<Address, Value>;
+}
+
+impl AddressLike for Address {
+    // Fails with the original value if it is not a valid address (i.e. it is negative)
+    fn from_value(value: Value) -> Result<Address, Value> {
+        value.try_into().map_err(|_| value)
+    }
+}
+
+// Input may run out, in which case None is returned
+pub trait IO {
+    fn input(&mut self) -> Option<Value>;
+    fn output(&mut self, _: Value);
+}
+
+// Inputs are queued until an instruction consumes them, and outputs until the computer hands them
+// over to whoever is running it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Hardware<M = PagedMemory> {
+    memory: M,
+    inputs: VecDeque<Value>,
+    outputs: VecDeque<Value>,
+    // Highest address written to so far, if any
+    #[serde(default)]
+    highest_write: Option<Address>,
+}
+
+impl<M: Memory> Hardware<M> {
+    pub fn new(program: Vec<Value>) -> Hardware<M> {
+        Hardware {
+            memory: M::load(program),
+            inputs: VecDeque::new(),
+            outputs: VecDeque::new(),
+            highest_write: None,
+        }
+    }
+
+    pub fn read(&self, location: Address) -> Value {
+        self.memory.read(location)
+    }
+
+    pub fn write(&mut self, location: Address, value: Value) {
+        self.memory.write(location, value);
+        self.highest_write = self.highest_write.max(Some(location));
+    }
+
+    pub fn highest_write(&self) -> Option<Address> {
+        self.highest_write
+    }
+
+    pub fn push_input(&mut self, value: Value) {
+        self.inputs.push_back(value);
+    }
+
+    pub fn input(&mut self) -> Option<Value> {
+        self.inputs.pop_front()
+    }
+
+    pub fn output(&mut self, value: Value) {
+        self.outputs.push_back(value);
+    }
+
+    pub fn pop_output(&mut self) -> Option<Value> {
+        self.outputs.pop_front()
+    }
+}
@@ -0,0 +1,366 @@
+use core::convert::{TryFrom, TryInto};
+use std::ops::Deref;
+
+use serde::{Deserialize, Serialize};
+
+use super::hardware::*;
+use super::memory::Memory;
+
+// Different types of instructions
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub enum Instruction {
+    Add,
+    Mul,
+    Input,
+    Output,
+    JumpIfTrue,
+    JumpIfFalse,
+    LessThan,
+    Equals,
+    RelativeBaseOffset,
+    Halt,
+    // An opcode added through a registry, which knows its arguments and how to execute it
+    Extension(Value),
+}
+
+// An instruction's arguments may be in-values (read from) or out-values (written to)
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum ArgumentType {
+    In,
+    Out,
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum ArgumentMode {
+    Immediate, // The value itself is the argument
+    Indexed,   // The value is the address of the argument
+    Relative,  // The value is at an address relative to a relative base
+}
+
+// In-values are just values, while out-values are addresses (where a value is stored).
+// Note that this means that in-arguments in indexed mode must first be dereferenced
+// to be used as an Argument.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Argument {
+    In(Value),
+    Out(Address),
+}
+
+// Map indicators to argument modes
+impl TryFrom<Value> for ArgumentMode {
+    type Error = Value;
+
+    fn try_from(x: Value) -> Result<Self, Self::Error> {
+        match x {
+            0 => Ok(ArgumentMode::Indexed),
+            1 => Ok(ArgumentMode::Immediate),
+            2 => Ok(ArgumentMode::Relative),
+            _ => Err(x),
+        }
+    }
+}
+
+impl ArgumentMode {
+    // Inverse of the mapping from indicators to argument modes
+    pub fn indicator(&self) -> Value {
+        match self {
+            ArgumentMode::Indexed => 0,
+            ArgumentMode::Immediate => 1,
+            ArgumentMode::Relative => 2,
+        }
+    }
+
+    // Extracts the mode of the 0-based nth argument from a raw instruction value, failing with the
+    // mode indicator if it is not a valid one
+    pub fn of_argument(instruction: Value, nth: usize) -> Result<ArgumentMode, Value> {
+        // In a string representation, the last two digits of the instruction value
+        // are the opcode (i.e. opcodes go from 0 to 99). We first remove those.
+        let mode_indicators = instruction / 100;
+
+        // Then, the mode for the 0-based nth argument is the nth digit from the
+        // right: the first argument is the units, second tenths, and so on.
+        let mode_indicator = (mode_indicators / 10_i64.pow(nth.try_into().unwrap())) % 10;
+
+        ArgumentMode::try_from(mode_indicator)
+    }
+}
+
+impl Argument {
+    // Extracts in-value or fails
+    pub fn get_input(&self) -> Value {
+        match self {
+            Argument::In(input) => *input,
+            _ => panic!("Non-input argument: {:?}", self),
+        }
+    }
+
+    // Extracts out-value or fails
+    pub fn get_output(&self) -> Address {
+        match self {
+            Argument::Out(output) => *output,
+            _ => panic!("Non-output argument: {:?}", self),
+        }
+    }
+}
+
+// Map value (opcode) to instruction
+impl TryFrom<Value> for Instruction {
+    type Error = Value;
+
+    fn try_from(x: Value) -> Result<Self, Self::Error> {
+        use Instruction::*;
+
+        // Opcodes go from 0 to 99, the rest of the value sets the argument modes
+        match x % 100 {
+            1 => Ok(Add),
+            2 => Ok(Mul),
+            3 => Ok(Input),
+            4 => Ok(Output),
+            5 => Ok(JumpIfTrue),
+            6 => Ok(JumpIfFalse),
+            7 => Ok(LessThan),
+            8 => Ok(Equals),
+            9 => Ok(RelativeBaseOffset),
+            99 => Ok(Halt),
+            _ => Err(x),
+        }
+    }
+}
+
+// No instruction takes more arguments than this
+pub const MAX_ARGUMENTS: usize = 3;
+
+// An instruction along with the modes of its arguments. These only depend on the instruction's raw
+// value, so they can be reused for as long as that value is not overwritten. Invalid modes are
+// kept so that the error is raised when (and if) the argument is resolved.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Decoded {
+    pub instruction: Instruction,
+    pub argument_types: &'static [ArgumentType],
+    pub modes: [Result<ArgumentMode, Value>; MAX_ARGUMENTS],
+}
+
+impl Decoded {
+    // Fails with the raw value if the opcode is unknown
+    pub fn new(value: Value) -> Result<Decoded, Value> {
+        let instruction = Instruction::try_from(value)?;
+
+        Ok(Decoded::with_argument_types(
+            value,
+            instruction,
+            instruction.argument_types(),
+        ))
+    }
+
+    // Decodes an opcode that is not built in, given its argument types
+    pub fn extension(value: Value, argument_types: &'static [ArgumentType]) -> Decoded {
+        Decoded::with_argument_types(value, Instruction::Extension(value % 100), argument_types)
+    }
+
+    fn with_argument_types(
+        value: Value,
+        instruction: Instruction,
+        argument_types: &'static [ArgumentType],
+    ) -> Decoded {
+        let mut modes = [Ok(ArgumentMode::Indexed); MAX_ARGUMENTS];
+        for (nth, mode) in modes.iter_mut().enumerate().take(argument_types.len()) {
+            *mode = ArgumentMode::of_argument(value, nth);
+        }
+
+        Decoded {
+            instruction,
+            argument_types,
+            modes,
+        }
+    }
+}
+
+// The resolved arguments of an instruction, stored inline so that executing instructions does not
+// allocate
+#[derive(Debug, Clone, Copy)]
+pub struct Arguments {
+    arguments: [Argument; MAX_ARGUMENTS],
+    len: usize,
+}
+
+impl Arguments {
+    pub fn new() -> Arguments {
+        Arguments {
+            arguments: [Argument::In(0); MAX_ARGUMENTS],
+            len: 0,
+        }
+    }
+
+    pub fn push(&mut self, argument: Argument) {
+        self.arguments[self.len] = argument;
+        self.len += 1;
+    }
+}
+
+impl Default for Arguments {
+    fn default() -> Arguments {
+        Arguments::new()
+    }
+}
+
+impl Deref for Arguments {
+    type Target = [Argument];
+
+    fn deref(&self) -> &[Argument] {
+        &self.arguments[..self.len]
+    }
+}
+
+pub enum RegisterChange {
+    ProgramCounter { new_value: Value },
+    RelativeBase { change: Value },
+    // The instruction could not be carried out (e.g. there was no input available), so the
+    // program counter must not move
+    Stall,
+}
+
+impl Instruction {
+    // Inverse of the mapping from values to instructions
+    pub fn opcode(&self) -> Value {
+        use Instruction::*;
+
+        match self {
+            Add => 1,
+            Mul => 2,
+            Input => 3,
+            Output => 4,
+            JumpIfTrue => 5,
+            JumpIfFalse => 6,
+            LessThan => 7,
+            Equals => 8,
+            RelativeBaseOffset => 9,
+            Halt => 99,
+            Extension(opcode) => *opcode,
+        }
+    }
+
+    // Builds the raw value of this instruction when its arguments have the given modes
+    pub fn encode(&self, modes: &[ArgumentMode]) -> Value {
+        modes
+            .iter()
+            .rev()
+            .fold(0, |indicators, mode| indicators * 10 + mode.indicator())
+            * 100
+            + self.opcode()
+    }
+
+    pub fn from_mnemonic(mnemonic: &str) -> Option<Instruction> {
+        (0..100)
+            .filter_map(|opcode| Instruction::try_from(opcode).ok())
+            .find(|instruction| instruction.mnemonic() == mnemonic)
+    }
+
+    // Short name used in listings
+    pub fn mnemonic(&self) -> &'static str {
+        use Instruction::*;
+
+        match self {
+            Add => "add",
+            Mul => "mul",
+            Input => "in",
+            Output => "out",
+            JumpIfTrue => "jt",
+            JumpIfFalse => "jf",
+            LessThan => "lt",
+            Equals => "eq",
+            RelativeBaseOffset => "arb",
+            Halt => "hlt",
+            Extension(_) => "ext",
+        }
+    }
+
+    // Map each instruction to its argument types. Those of extensions are only known to the
+    // registry they were added to.
+    pub fn argument_types(&self) -> &'static [ArgumentType] {
+        use ArgumentType::*;
+        use Instruction::*;
+
+        match self {
+            Add => &[In, In, Out],
+            Mul => &[In, In, Out],
+            Input => &[Out],
+            Output => &[In],
+            JumpIfTrue => &[In, In],
+            JumpIfFalse => &[In, In],
+            LessThan => &[In, In, Out],
+            Equals => &[In, In, Out],
+            RelativeBaseOffset => &[In],
+            Halt => &[],
+            Extension(_) => &[],
+        }
+    }
+
+    // Execute an instruction on a hardware
+    pub fn exec<M: Memory>(
+        &self,
+        arguments: &[Argument],
+        hardware: &mut Hardware<M>,
+    ) -> Option<RegisterChange> {
+        use Instruction::*;
+
+        let mut register_change = None;
+
+        match self {
+            Add | Mul | LessThan | Equals => {
+                // Implements an instruction that consists of a binary operation that
+                // writes its result to memory
+                let mut write_binary_operation = |operation: fn(Value, Value) -> Value| {
+                    let lhs = arguments[0].get_input();
+                    let rhs = arguments[1].get_input();
+                    let destination = arguments[2].get_output();
+
+                    hardware.write(destination, operation(lhs, rhs));
+                };
+
+                match self {
+                    Add => write_binary_operation(|lhs, rhs| lhs + rhs),
+                    Mul => write_binary_operation(|lhs, rhs| lhs * rhs),
+                    LessThan => write_binary_operation(|lhs, rhs| if lhs < rhs { 1 } else { 0 }),
+                    Equals => write_binary_operation(|lhs, rhs| if lhs == rhs { 1 } else { 0 }),
+                    _ => unreachable!("Missing match for binary instruction {:?}", self),
+                }
+            }
+            JumpIfTrue | JumpIfFalse => {
+                // Implements an instruction that performs an absolute jump if a
+                // function applied on a value is true
+                let mut jump_if = |condition: fn(Value) -> bool| {
+                    let value = arguments[0].get_input();
+                    let destination = arguments[1].get_input();
+
+                    if condition(value) {
+                        register_change = Some(RegisterChange::ProgramCounter {
+                            new_value: destination,
+                        });
+                    }
+                };
+
+                match self {
+                    JumpIfTrue => jump_if(|value| value != 0),
+                    JumpIfFalse => jump_if(|value| value == 0),
+                    _ => unreachable!("Missing match for conditional jump instruction {:?}", self),
+                }
+            }
+            RelativeBaseOffset => {
+                register_change = Some(RegisterChange::RelativeBase {
+                    change: arguments[0].get_input(),
+                });
+            }
+            Input => match hardware.input() {
+                Some(input) => hardware.write(arguments[0].get_output(), input),
+                None => register_change = Some(RegisterChange::Stall),
+            },
+            Output => {
+                hardware.output(arguments[0].get_input());
+            }
+            Halt => {}
+            Extension(_) => unreachable!("Extensions are executed by their registry"),
+        }
+
+        register_change
+    }
+}
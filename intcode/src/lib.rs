@@ -0,0 +1,961 @@
+use core::convert::TryFrom;
+use std::io::{self, Write};
+use std::num::ParseIntError;
+
+pub mod hardware;
+pub use hardware::{Address, AddressLike, Hardware, Value, IO};
+
+pub mod memory;
+pub use memory::{Memory, PagedMemory};
+
+pub mod instruction;
+pub use instruction::Instruction;
+use instruction::*;
+
+pub mod error;
+pub use error::IntcodeError;
+
+pub mod analysis;
+pub mod assembler;
+pub mod debugger;
+pub mod disassembler;
+pub mod network;
+pub mod profiler;
+use profiler::Profiler;
+pub mod registry;
+use registry::Registry;
+pub mod scheduler;
+pub mod snapshot;
+pub mod symbolic;
+use snapshot::Snapshot;
+pub mod trace;
+use trace::{Step, Tracer};
+
+// Parses a comma-separated program, as found in puzzle inputs
+pub fn parse_program(source: &str) -> Result<Vec<Value>, ParseIntError> {
+    source
+        .split(',')
+        .map(|x| x.trim().parse::<Value>())
+        .collect()
+}
+
+struct Console;
+
+impl IO for Console {
+    fn input(&mut self) -> Option<Value> {
+        print!("PROMPT: ");
+        io::stdout().flush().unwrap();
+
+        let mut raw_input = String::new();
+        if io::stdin().read_line(&mut raw_input).unwrap() == 0 {
+            // End of file
+            return None;
+        }
+
+        raw_input.trim().parse::<Value>().ok()
+    }
+
+    fn output(&mut self, value: Value) {
+        println!("PRINT: {}", value);
+    }
+}
+
+// Reasons for which a computer stops running and hands control back to its caller
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Event {
+    NeedsInput,
+    Output(Value),
+    Halted,
+}
+
+pub struct Computer<'hw, M = PagedMemory> {
+    hardware: Hardware<M>,
+    io: &'hw mut dyn IO,
+    tracer: Option<&'hw mut dyn Tracer>,
+    profiler: Option<&'hw mut Profiler>,
+    registry: Option<&'hw Registry<M>>,
+    program_counter: Address,
+    relative_base: Address,
+    // Decoded instructions for each address of the program image. Entries are cleared when their
+    // address is written to, since programs may modify their own code.
+    decoded: Vec<Option<Decoded>>,
+}
+
+impl<'hw> Computer<'hw> {
+    // The IO is only used by run: callers driving the computer through run_until_event supply
+    // inputs and receive outputs directly
+    pub fn new(program: Vec<Value>, io: Option<&'hw mut dyn IO>) -> Computer<'hw> {
+        Computer::with_memory(program, io)
+    }
+}
+
+impl<'hw, M: Memory> Computer<'hw, M> {
+    // Same as new, but with a memory backend of choice
+    pub fn with_memory(program: Vec<Value>, io: Option<&'hw mut dyn IO>) -> Computer<'hw, M> {
+        Computer {
+            decoded: vec![None; program.len()],
+            hardware: Hardware::new(program),
+            io: io.unwrap_or_else(|| {
+                // The console is zero-sized, so leaking it does not allocate
+                Box::leak(Box::new(Console))
+            }),
+            tracer: None,
+            profiler: None,
+            registry: None,
+            program_counter: 0,
+            relative_base: 0,
+        }
+    }
+
+    // Captures the full state of the machine (memory, registers and pending IO), but not the IO
+    // handle or tracer
+    pub fn snapshot(&self) -> Snapshot<M>
+    where
+        M: Clone,
+    {
+        Snapshot {
+            hardware: self.hardware.clone(),
+            program_counter: self.program_counter,
+            relative_base: self.relative_base,
+            image_size: self.decoded.len(),
+        }
+    }
+
+    // Returns the machine to a previously captured state. Snapshots can be restored into any
+    // computer, which allows for forking execution.
+    pub fn restore(&mut self, snapshot: &Snapshot<M>)
+    where
+        M: Clone,
+    {
+        self.hardware = snapshot.hardware.clone();
+        self.program_counter = snapshot.program_counter;
+        self.relative_base = snapshot.relative_base;
+
+        // The restored image may not be the one this computer was created with
+        self.decoded = vec![None; snapshot.image_size];
+
+        if let Some(profiler) = self.profiler.as_mut() {
+            profiler.load(snapshot.image_size);
+        }
+    }
+
+    // Every instruction executed from now on will be reported to the tracer
+    pub fn set_tracer(&mut self, tracer: &'hw mut dyn Tracer) {
+        self.tracer = Some(tracer);
+    }
+
+    // Every instruction executed from now on will be counted by the profiler
+    pub fn set_profiler(&mut self, profiler: &'hw mut Profiler) {
+        profiler.load(self.decoded.len());
+        self.profiler = Some(profiler);
+    }
+
+    // Opcodes in the registry can be run in addition to the built-in ones
+    pub fn set_registry(&mut self, registry: &'hw Registry<M>) {
+        self.registry = Some(registry);
+
+        // Opcodes that were unknown so far may now be extensions
+        self.invalidate_all();
+    }
+
+    pub fn program_counter(&self) -> Address {
+        self.program_counter
+    }
+
+    pub fn relative_base(&self) -> Address {
+        self.relative_base
+    }
+
+    // Overwrites a memory cell, typically to set up a program's parameters before running it
+    pub fn patch(&mut self, address: Address, value: Value) {
+        self.hardware.write(address, value);
+
+        // The cell may hold an instruction that was already decoded
+        if let Some(entry) = self.decoded.get_mut(address) {
+            *entry = None;
+        }
+    }
+
+    // Reads a memory cell, typically to get a program's results once it halts
+    pub fn read(&self, address: Address) -> Value {
+        self.hardware.read(address)
+    }
+
+    // Queues a value to be consumed by a future input instruction
+    pub fn provide_input(&mut self, value: Value) {
+        self.hardware.push_input(value);
+    }
+
+    // The raw value of the current instruction, which includes the argument modes
+    fn instruction_value(&self) -> Value {
+        self.hardware.read(self.program_counter)
+    }
+
+    // Decodes the current instruction, reusing a previous decoding of the same address if there is
+    // one
+    fn decode(&mut self) -> Result<Decoded, IntcodeError> {
+        if let Some(Some(decoded)) = self.decoded.get(self.program_counter) {
+            return Ok(*decoded);
+        }
+
+        let value = self.instruction_value();
+        let decoded = Decoded::new(value)
+            .ok()
+            .or_else(|| self.registry?.decode(value))
+            .ok_or(IntcodeError::UnknownOpcode {
+                program_counter: self.program_counter,
+                instruction: value,
+            })?;
+
+        if let Some(entry) = self.decoded.get_mut(self.program_counter) {
+            *entry = Some(decoded);
+        }
+
+        Ok(decoded)
+    }
+
+    // Forgets the decoded instructions at addresses that were just written to
+    fn invalidate(&mut self, arguments: &[Argument]) {
+        for argument in arguments {
+            if let Argument::Out(address) = argument {
+                if let Some(entry) = self.decoded.get_mut(*address) {
+                    *entry = None;
+                }
+            }
+        }
+    }
+
+    // Forgets all decoded instructions
+    fn invalidate_all(&mut self) {
+        for decoded in self.decoded.iter_mut() {
+            *decoded = None;
+        }
+    }
+
+    // Reads the value of the nth argument for the current instruction (0-based)
+    fn argument_value(&self, nth: usize) -> Value {
+        self.hardware.read(self.program_counter + nth + 1)
+    }
+
+    fn argument_mode(&self, decoded: &Decoded, nth: usize) -> Result<ArgumentMode, IntcodeError> {
+        decoded.modes[nth].map_err(|mode| IntcodeError::InvalidMode {
+            program_counter: self.program_counter,
+            instruction: self.instruction_value(),
+            argument: nth,
+            mode,
+        })
+    }
+
+    fn address(&self, value: Value) -> Result<Address, IntcodeError> {
+        Address::from_value(value).map_err(|address| IntcodeError::NegativeAddress {
+            program_counter: self.program_counter,
+            instruction: self.instruction_value(),
+            address,
+        })
+    }
+
+    fn relative_address(&self, offset: Value) -> Result<Address, IntcodeError> {
+        let address = Value::try_from(self.relative_base)
+            .ok()
+            .and_then(|relative_base| relative_base.checked_add(offset))
+            .ok_or(IntcodeError::AddressOverflow {
+                program_counter: self.program_counter,
+                instruction: self.instruction_value(),
+                relative_base: self.relative_base,
+                offset,
+            })?;
+
+        self.address(address)
+    }
+
+    fn relative_jump_forward(&mut self, distance: usize) {
+        self.program_counter += distance;
+    }
+
+    fn absolute_jump(&mut self, destination: Value) -> Result<(), IntcodeError> {
+        self.program_counter = self.address(destination)?;
+        Ok(())
+    }
+
+    fn move_relative_base(&mut self, change: Value) -> Result<(), IntcodeError> {
+        let distance = usize::try_from(change.unsigned_abs()).ok();
+        let new_value = distance.and_then(|distance| {
+            if change > 0 {
+                self.relative_base.checked_add(distance)
+            } else {
+                self.relative_base.checked_sub(distance)
+            }
+        });
+
+        self.relative_base = new_value.ok_or(IntcodeError::RelativeBaseUnderflow {
+            program_counter: self.program_counter,
+            instruction: self.instruction_value(),
+            relative_base: self.relative_base,
+            change,
+        })?;
+
+        Ok(())
+    }
+
+    // Runs the program until it halts, using the IO to resolve all input and output events
+    pub fn run(&mut self) -> Result<(), IntcodeError> {
+        loop {
+            match self.next_event()? {
+                Event::NeedsInput => self.input_from_io()?,
+                Event::Output(value) => self.io.output(value),
+                Event::Halted => break Ok(()),
+            }
+        }
+    }
+
+    // Executes a single instruction, using the IO to resolve input and output
+    pub fn step_with_io(&mut self) -> Result<Instruction, IntcodeError> {
+        let instruction = match self.step() {
+            Err(IntcodeError::InputExhausted { .. }) => {
+                self.input_from_io()?;
+                self.step()?
+            }
+            result => result?,
+        };
+
+        while let Some(value) = self.hardware.pop_output() {
+            self.io.output(value);
+        }
+
+        Ok(instruction)
+    }
+
+    fn input_from_io(&mut self) -> Result<(), IntcodeError> {
+        let value = self.io.input().ok_or(IntcodeError::InputExhausted {
+            program_counter: self.program_counter,
+            instruction: self.instruction_value(),
+        })?;
+
+        self.provide_input(value);
+        Ok(())
+    }
+
+    // Runs the program until it either requires input that has not been provided, produces an
+    // output or halts. Execution can be resumed by calling this again (after providing input, if
+    // that was the reason it stopped).
+    pub fn run_until_event(&mut self) -> Result<Event, IntcodeError> {
+        let event = self.next_event()?;
+
+        // The computer cannot go on until its caller comes back with the input
+        if event == Event::NeedsInput {
+            self.io_wait();
+        }
+
+        Ok(event)
+    }
+
+    // Same as run_until_event, for callers that have the input ready as soon as it is needed
+    fn next_event(&mut self) -> Result<Event, IntcodeError> {
+        loop {
+            // A single instruction may produce several outputs, which are all handed over before
+            // anything else happens
+            if let Some(value) = self.hardware.pop_output() {
+                return Ok(Event::Output(value));
+            }
+
+            // Running out of input is not an error here, since the caller can provide more
+            let instruction = match self.step() {
+                Err(IntcodeError::InputExhausted { .. }) => return Ok(Event::NeedsInput),
+                result => result?,
+            };
+
+            if instruction == Instruction::Halt {
+                return Ok(Event::Halted);
+            }
+        }
+    }
+
+    // Counts a wait on input that was not ready when it was needed
+    fn io_wait(&mut self) {
+        if let Some(profiler) = self.profiler.as_mut() {
+            profiler.io_wait();
+        }
+    }
+
+    // Resolves the arguments of the current instruction: in-arguments are read from memory if
+    // needed, while out-arguments are turned into the address they will be written to
+    fn arguments(&self, decoded: &Decoded) -> Result<Arguments, IntcodeError> {
+        let mut arguments = Arguments::new();
+
+        for (index, argument_type) in decoded.argument_types.iter().enumerate() {
+            let value = self.argument_value(index);
+            let mode = self.argument_mode(decoded, index)?;
+
+            arguments.push(match argument_type {
+                ArgumentType::In => Argument::In(match mode {
+                    ArgumentMode::Immediate => value,
+                    ArgumentMode::Indexed => self.hardware.read(self.address(value)?),
+                    ArgumentMode::Relative => self.hardware.read(self.relative_address(value)?),
+                }),
+                ArgumentType::Out => Argument::Out(match mode {
+                    ArgumentMode::Immediate => {
+                        return Err(IntcodeError::ImmediateWrite {
+                            program_counter: self.program_counter,
+                            instruction: self.instruction_value(),
+                            argument: index,
+                        })
+                    }
+                    ArgumentMode::Indexed => self.address(value)?,
+                    ArgumentMode::Relative => self.relative_address(value)?,
+                }),
+            });
+        }
+
+        Ok(arguments)
+    }
+
+    pub fn step(&mut self) -> Result<Instruction, IntcodeError> {
+        // Kept for tracing, since executing the instruction might overwrite it
+        let (program_counter, instruction_value) = (self.program_counter, self.instruction_value());
+        let decoded = self.decode()?;
+        let instruction = decoded.instruction;
+        let arguments = self.arguments(&decoded)?;
+
+        // Run instruction
+        let register_change = match (instruction, self.registry) {
+            (Instruction::Extension(opcode), Some(registry)) => {
+                let change = (registry.get(opcode).unwrap().exec)(&arguments, &mut self.hardware);
+
+                // Extensions get the whole hardware, so they may have written anywhere
+                self.invalidate_all();
+                change
+            }
+            _ => {
+                let change = instruction.exec(&arguments, &mut self.hardware);
+                self.invalidate(&arguments);
+                change
+            }
+        };
+
+        // An instruction may requests changes to certain registers as part of its operation, which
+        // are now carried out
+
+        match register_change {
+            Some(RegisterChange::ProgramCounter { new_value }) => self.absolute_jump(new_value)?,
+            Some(RegisterChange::RelativeBase { change }) => {
+                self.move_relative_base(change)?;
+                self.relative_jump_forward(1 + arguments.len());
+            }
+            Some(RegisterChange::Stall) => {
+                return Err(IntcodeError::InputExhausted {
+                    program_counter: self.program_counter,
+                    instruction: self.instruction_value(),
+                });
+            }
+            // Halting leaves the program counter on the halt instruction, so that resuming
+            // execution halts again
+            None if instruction == Instruction::Halt => {}
+            None => self.relative_jump_forward(1 + arguments.len()),
+        }
+
+        if let Some(profiler) = self.profiler.as_mut() {
+            let mnemonic = match (instruction, self.registry) {
+                (Instruction::Extension(opcode), Some(registry)) => {
+                    registry.mnemonic(opcode).unwrap()
+                }
+                _ => instruction.mnemonic(),
+            };
+
+            profiler.record(
+                program_counter,
+                instruction,
+                mnemonic,
+                self.hardware.highest_write(),
+            );
+        }
+
+        if self.tracer.is_some() {
+            self.trace(program_counter, instruction_value, instruction, arguments);
+        }
+
+        Ok(instruction)
+    }
+
+    // Reports an executed instruction to the tracer. All memory writes are done to out-arguments,
+    // so their values can be read back from memory.
+    fn trace(
+        &mut self,
+        program_counter: Address,
+        instruction_value: Value,
+        instruction: Instruction,
+        arguments: Arguments,
+    ) {
+        let writes = arguments
+            .iter()
+            .filter_map(|argument| match argument {
+                Argument::Out(address) => Some((*address, self.hardware.read(*address))),
+                Argument::In(_) => None,
+            })
+            .collect::<Vec<_>>();
+
+        let step = Step {
+            program_counter,
+            instruction: instruction_value,
+            input: match instruction {
+                Instruction::Input => Some(writes[0].1),
+                _ => None,
+            },
+            output: match (instruction, &arguments[..]) {
+                (Instruction::Output, [Argument::In(value)]) => Some(*value),
+                _ => None,
+            },
+            arguments: arguments.to_vec(),
+            writes,
+        };
+
+        self.tracer.as_mut().unwrap().trace(&step);
+    }
+}
+
+#[cfg(test)]
+mod instructions {
+    use super::*;
+
+    fn step(program: Vec<Value>, expected_instr: Instruction, expected_memory: Vec<Value>) {
+        let mut computer = Computer::new(program, None);
+        let instruction = computer.step().unwrap();
+
+        assert_eq!(instruction, expected_instr);
+
+        let actual_memory: Vec<_> = (0..expected_memory.len())
+            .map(|index| computer.read(index))
+            .collect();
+
+        assert_eq!(actual_memory, expected_memory);
+    }
+
+    #[test]
+    fn patched_memory() {
+        // Loops forever until the jump is patched into a halt, after both instructions have been
+        // decoded
+        let mut computer = Computer::new(vec![1101, 1, 0, 9, 1105, 1, 0, 0, 0, 0], None);
+        computer.step().unwrap();
+        computer.step().unwrap();
+
+        computer.patch(4, 99);
+        computer.patch(1, 41);
+        computer.run().unwrap();
+
+        assert_eq!(computer.read(9), 41);
+        assert_eq!(computer.program_counter(), 4);
+    }
+
+    #[test]
+    fn add() {
+        step(vec![1, 0, 0, 0], Instruction::Add, vec![2, 0, 0, 0])
+    }
+
+    #[test]
+    fn mul() {
+        step(vec![2, 3, 0, 3], Instruction::Mul, vec![2, 3, 0, 6])
+    }
+}
+
+#[cfg(test)]
+mod programs {
+    use super::*;
+
+    fn run(program: Vec<Value>, expected_memory: Vec<Value>) {
+        let mut computer = Computer::new(program, None);
+        computer.run().unwrap();
+
+        let actual_memory: Vec<_> = (0..expected_memory.len())
+            .map(|index| computer.hardware.read(index))
+            .collect();
+
+        assert_eq!(actual_memory, expected_memory);
+    }
+
+    #[test]
+    fn add() {
+        run(
+            vec![1, 1, 1, 4, 99, 5, 6, 0, 99],
+            vec![30, 1, 1, 4, 2, 5, 6, 0, 99],
+        )
+    }
+
+    #[test]
+    fn negative_add() {
+        run(vec![1101, 100, -1, 4, 0], vec![1101, 100, -1, 4, 99])
+    }
+
+    #[test]
+    fn multiply() {
+        run(vec![2, 4, 4, 5, 99, 0], vec![2, 4, 4, 5, 99, 9801])
+    }
+
+    #[test]
+    fn multiply_with_argument_modes() {
+        run(vec![1002, 4, 3, 4, 33], vec![1002, 4, 3, 4, 99])
+    }
+}
+
+#[cfg(test)]
+mod programs_io {
+    use super::*;
+
+    struct FixedIO {
+        input_value: Value,
+        output_values: Vec<Value>,
+    }
+
+    impl FixedIO {
+        fn new(input: Value) -> FixedIO {
+            FixedIO {
+                input_value: input,
+                output_values: Vec::<_>::new(),
+            }
+        }
+    }
+
+    impl IO for FixedIO {
+        fn input(&mut self) -> Option<Value> {
+            Some(self.input_value)
+        }
+
+        fn output(&mut self, value: Value) {
+            self.output_values.push(value);
+        }
+    }
+
+    fn run_io_with<M: Memory>(program: &[Value], input_value: Value, expected_output: &[Value]) {
+        let mut fixed_io = FixedIO::new(input_value);
+        let mut computer = Computer::<M>::with_memory(program.to_vec(), Some(&mut fixed_io));
+        computer.run().unwrap();
+
+        assert_eq!(fixed_io.output_values, *expected_output);
+    }
+
+    // All memory backends must behave the same
+    fn run_io(program: &[Value], input_value: Value, expected_output: &[Value]) {
+        run_io_with::<PagedMemory>(program, input_value, expected_output);
+        run_io_with::<std::collections::HashMap<Address, Value>>(
+            program,
+            input_value,
+            expected_output,
+        );
+    }
+
+    #[test]
+    fn basic_io() {
+        run_io(&[3, 0, 4, 0, 99], 5, &[5])
+    }
+
+    #[test]
+    fn io_equal_position_mode() {
+        // Test if input equals 8
+        let program = vec![3, 9, 8, 9, 10, 9, 4, 9, 99, -1, 8];
+        run_io(&program, 7, &[0]);
+        run_io(&program, 8, &[1]);
+        run_io(&program, 9, &[0]);
+    }
+
+    #[test]
+    fn io_equal_immediate_mode() {
+        // Test if input equals 8
+        let program = vec![3, 3, 1108, -1, 8, 3, 4, 3, 99];
+        run_io(&program, 7, &[0]);
+        run_io(&program, 8, &[1]);
+        run_io(&program, 9, &[0]);
+    }
+
+    #[test]
+    fn io_less_than_position_mode() {
+        // Test if input is less than 8
+        let program = vec![3, 9, 7, 9, 10, 9, 4, 9, 99, -1, 8];
+        run_io(&program, 6, &[1]);
+        run_io(&program, 7, &[1]);
+        run_io(&program, 8, &[0]);
+    }
+
+    #[test]
+    fn io_less_than_immediate_mode() {
+        // Test if input is less than 8
+        let program = vec![3, 3, 1107, -1, 8, 3, 4, 3, 99];
+        run_io(&program, 6, &[1]);
+        run_io(&program, 7, &[1]);
+        run_io(&program, 8, &[0]);
+    }
+
+    #[test]
+    fn io_jump_position_mode() {
+        // Test if input is true
+        let program = vec![3, 12, 6, 12, 15, 1, 13, 14, 13, 4, 13, 99, -1, 0, 1, 9];
+        run_io(&program, 0, &[0]);
+        run_io(&program, 1, &[1]);
+        run_io(&program, 2, &[1]);
+    }
+
+    #[test]
+    fn io_jump_immediate_mode() {
+        // Test if input is true
+        let program = vec![3, 3, 1105, -1, 9, 1101, 0, 0, 12, 4, 12, 99, 1];
+        run_io(&program, 0, &[0]);
+        run_io(&program, 1, &[1]);
+        run_io(&program, 2, &[1]);
+    }
+
+    #[test]
+    fn io_complex() {
+        // Test if input is less, equal or greater to 8
+        let program = vec![
+            3, 21, 1008, 21, 8, 20, 1005, 20, 22, 107, 8, 21, 20, 1006, 20, 31, 1106, 0, 36, 98, 0,
+            0, 1002, 21, 125, 20, 4, 20, 1105, 1, 46, 104, 999, 1105, 1, 46, 1101, 1000, 1, 20, 4,
+            20, 1105, 1, 46, 98, 99,
+        ];
+
+        run_io(&program, 6, &[999]);
+        run_io(&program, 7, &[999]);
+        run_io(&program, 8, &[1000]);
+        run_io(&program, 9, &[1001]);
+        run_io(&program, 10, &[1001]);
+    }
+
+    #[test]
+    fn io_assembled_countdown() {
+        // Outputs all numbers from the input down to 1
+        let program = assembler::assemble(
+            "
+            in   [n]
+            loop: jf [n], #end
+                  out [n]
+                  add [n], #-1, [n]
+                  jt #1, #loop
+            end:  hlt
+            n:    data 0
+            ",
+        )
+        .unwrap();
+
+        run_io(&program, 3, &[3, 2, 1]);
+        run_io(&program, 0, &[]);
+    }
+
+    #[test]
+    fn io_self_modifying_loop() {
+        // The first instruction is executed twice, as an addition and then as a multiplication
+        let program = assembler::assemble(
+            "
+            loop: add #3, #4, [result]
+                  out [result]
+                  eq [loop], #1102, [done]
+                  jt [done], #end
+                  add [loop], #1, [loop]
+                  jt #1, #loop
+            end:  hlt
+            result: data 0
+            done:   data 0
+            ",
+        )
+        .unwrap();
+
+        run_io(&program, 0, &[7, 12]);
+    }
+
+    #[test]
+    fn large_memory_relative_base_quine() {
+        let program = vec![
+            109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+        ];
+
+        run_io(&program, 0, &program);
+    }
+
+    #[test]
+    fn compute_large_number() {
+        let program = vec![1102, 34915192, 34915192, 7, 4, 7, 99, 0];
+
+        run_io(&program, 0, &[1219070632396864]);
+    }
+
+    #[test]
+    fn output_large_number() {
+        let program = vec![104, 1125899906842624, 99];
+
+        run_io(&program, 0, &[1125899906842624]);
+    }
+}
+
+#[cfg(test)]
+mod errors {
+    use super::*;
+
+    struct NoIO;
+
+    impl IO for NoIO {
+        fn input(&mut self) -> Option<Value> {
+            None
+        }
+
+        fn output(&mut self, _: Value) {}
+    }
+
+    fn run_err(program: Vec<Value>, expected_error: IntcodeError) {
+        let mut io = NoIO;
+        let mut computer = Computer::new(program, Some(&mut io));
+
+        assert_eq!(computer.run(), Err(expected_error));
+    }
+
+    #[test]
+    fn unknown_opcode() {
+        run_err(
+            vec![1101, 1, 1, 0, 42],
+            IntcodeError::UnknownOpcode {
+                program_counter: 4,
+                instruction: 42,
+            },
+        )
+    }
+
+    #[test]
+    fn invalid_mode() {
+        run_err(
+            vec![3101, 1, 1, 0, 99],
+            IntcodeError::InvalidMode {
+                program_counter: 0,
+                instruction: 3101,
+                argument: 1,
+                mode: 3,
+            },
+        )
+    }
+
+    #[test]
+    fn immediate_write() {
+        run_err(
+            vec![11101, 1, 1, 0, 99],
+            IntcodeError::ImmediateWrite {
+                program_counter: 0,
+                instruction: 11101,
+                argument: 2,
+            },
+        )
+    }
+
+    #[test]
+    fn negative_address() {
+        run_err(
+            vec![1, -1, 0, 0, 99],
+            IntcodeError::NegativeAddress {
+                program_counter: 0,
+                instruction: 1,
+                address: -1,
+            },
+        )
+    }
+
+    #[test]
+    fn negative_jump() {
+        run_err(
+            vec![1105, 1, -3],
+            IntcodeError::NegativeAddress {
+                program_counter: 0,
+                instruction: 1105,
+                address: -3,
+            },
+        )
+    }
+
+    #[test]
+    fn relative_base_underflow() {
+        run_err(
+            vec![109, 2, 109, -3, 99],
+            IntcodeError::RelativeBaseUnderflow {
+                program_counter: 2,
+                instruction: 109,
+                relative_base: 2,
+                change: -3,
+            },
+        )
+    }
+
+    #[test]
+    fn relative_base_extremes() {
+        run_err(
+            vec![109, i64::MIN, 99],
+            IntcodeError::RelativeBaseUnderflow {
+                program_counter: 0,
+                instruction: 109,
+                relative_base: 0,
+                change: i64::MIN,
+            },
+        );
+        run_err(
+            vec![109, i64::MAX, 204, 1, 99],
+            IntcodeError::AddressOverflow {
+                program_counter: 2,
+                instruction: 204,
+                relative_base: i64::MAX as usize,
+                offset: 1,
+            },
+        )
+    }
+
+    #[test]
+    fn input_exhausted() {
+        run_err(
+            vec![3, 0, 99],
+            IntcodeError::InputExhausted {
+                program_counter: 0,
+                instruction: 3,
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod events {
+    use super::*;
+
+    #[test]
+    fn needs_input() {
+        let mut computer = Computer::new(vec![3, 0, 4, 0, 99], None);
+
+        assert_eq!(computer.run_until_event(), Ok(Event::NeedsInput));
+        // Nothing happens until input is provided
+        assert_eq!(computer.run_until_event(), Ok(Event::NeedsInput));
+
+        computer.provide_input(42);
+        assert_eq!(computer.run_until_event(), Ok(Event::Output(42)));
+        assert_eq!(computer.run_until_event(), Ok(Event::Halted));
+    }
+
+    #[test]
+    fn queued_inputs() {
+        let mut computer = Computer::new(vec![3, 11, 3, 12, 1, 11, 12, 13, 4, 13, 99], None);
+
+        computer.provide_input(3);
+        computer.provide_input(4);
+        assert_eq!(computer.run_until_event(), Ok(Event::Output(7)));
+    }
+
+    #[test]
+    fn halted_stays_halted() {
+        let mut computer = Computer::new(vec![104, 1, 99], None);
+
+        assert_eq!(computer.run_until_event(), Ok(Event::Output(1)));
+        assert_eq!(computer.run_until_event(), Ok(Event::Halted));
+        assert_eq!(computer.run_until_event(), Ok(Event::Halted));
+    }
+
+    #[test]
+    fn quine_outputs() {
+        let program = vec![
+            109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+        ];
+        let mut computer = Computer::new(program.clone(), None);
+
+        let mut outputs = Vec::new();
+        while let Event::Output(value) = computer.run_until_event().unwrap() {
+            outputs.push(value);
+        }
+
+        assert_eq!(outputs, program);
+    }
+}
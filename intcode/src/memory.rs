@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::hardware::*;
+
+// Storage for a computer's memory. Memory is infinite: cells that were never written to read as
+// zero.
+pub trait Memory {
+    // Memory holding a program at address zero
+    fn load(program: Vec<Value>) -> Self;
+    fn read(&self, address: Address) -> Value;
+    fn write(&mut self, address: Address, value: Value);
+}
+
+// The simplest backend, in which every access is a hash lookup
+impl Memory for HashMap<Address, Value> {
+    fn load(program: Vec<Value>) -> Self {
+        program.into_iter().enumerate().collect()
+    }
+
+    fn read(&self, address: Address) -> Value {
+        *self.get(&address).unwrap_or(&0)
+    }
+
+    fn write(&mut self, address: Address, value: Value) {
+        self.insert(address, value);
+    }
+}
+
+// Number of cells in each page of PagedMemory
+const PAGE_SIZE: usize = 1024;
+
+// Keeps the program image in a dense vector, since that is where nearly all accesses go. Addresses
+// past it are grouped into pages, which are only allocated once written to.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PagedMemory {
+    image: Vec<Value>,
+    pages: HashMap<usize, Vec<Value>>,
+}
+
+impl Memory for PagedMemory {
+    fn load(program: Vec<Value>) -> Self {
+        PagedMemory {
+            image: program,
+            pages: HashMap::new(),
+        }
+    }
+
+    fn read(&self, address: Address) -> Value {
+        match self.image.get(address) {
+            Some(value) => *value,
+            None => self
+                .pages
+                .get(&(address / PAGE_SIZE))
+                .map_or(0, |page| page[address % PAGE_SIZE]),
+        }
+    }
+
+    fn write(&mut self, address: Address, value: Value) {
+        match self.image.get_mut(address) {
+            Some(cell) => *cell = value,
+            None => {
+                self.pages
+                    .entry(address / PAGE_SIZE)
+                    .or_insert_with(|| vec![0; PAGE_SIZE])[address % PAGE_SIZE] = value
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod memories {
+    use super::*;
+
+    fn zero_initialised<M: Memory>() {
+        let memory = M::load(vec![1, 2, 3]);
+
+        assert_eq!(memory.read(0), 1);
+        assert_eq!(memory.read(2), 3);
+        assert_eq!(memory.read(3), 0);
+        assert_eq!(memory.read(1 << 40), 0);
+    }
+
+    fn writes<M: Memory>() {
+        let mut memory = M::load(vec![1, 2, 3]);
+
+        let addresses = [1, 3, PAGE_SIZE - 1, PAGE_SIZE, 5 * PAGE_SIZE + 7, 1 << 40];
+        for (value, address) in addresses.iter().enumerate() {
+            memory.write(*address, value as Value + 10);
+        }
+
+        for (value, address) in addresses.iter().enumerate() {
+            assert_eq!(memory.read(*address), value as Value + 10);
+        }
+
+        assert_eq!(memory.read(0), 1);
+        assert_eq!(memory.read(2), 3);
+        assert_eq!(memory.read(PAGE_SIZE + 1), 0);
+    }
+
+    #[test]
+    fn hash_map() {
+        zero_initialised::<HashMap<Address, Value>>();
+        writes::<HashMap<Address, Value>>();
+    }
+
+    #[test]
+    fn paged() {
+        zero_initialised::<PagedMemory>();
+        writes::<PagedMemory>();
+    }
+}
@@ -0,0 +1,428 @@
+use core::convert::TryFrom;
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::error::Error;
+use std::fmt;
+use std::rc::Rc;
+use std::task::Poll;
+
+use super::scheduler::{AsyncIO, Scheduler, SchedulerError, Status};
+use super::*;
+
+// Number of consecutive empty reads after which a machine is considered idle
+const IDLE_READS: usize = 2;
+
+// Value read by machines whose packet queue is empty
+const NO_PACKET: Value = -1;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Packet {
+    pub destination: Value,
+    pub x: Value,
+    pub y: Value,
+}
+
+// Things hooks are notified of
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NetworkEvent {
+    // A packet was sent to the hook's address
+    Packet(Packet),
+    // All queues are empty and all machines are waiting for packets
+    Idle,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Response {
+    Continue,
+    Send(Vec<Packet>),
+    Stop,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NetworkError {
+    Scheduler(SchedulerError),
+    // Neither a machine nor a hook has the packet's destination address
+    UnknownDestination(Packet),
+}
+
+impl fmt::Display for NetworkError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NetworkError::Scheduler(error) => write!(f, "{}", error),
+            NetworkError::UnknownDestination(packet) => {
+                write!(f, "packet sent to unknown address {}", packet.destination)
+            }
+        }
+    }
+}
+
+impl Error for NetworkError {}
+
+// State shared by all machines in the network
+struct Bus {
+    queues: Vec<VecDeque<Value>>,
+    // Packets sent by machines that are yet to be routed
+    outbox: Vec<Packet>,
+    // Consecutive empty reads done by each machine
+    empty_reads: Vec<usize>,
+}
+
+// Feeds a machine its address, then the packets sent to it. Outputs are grouped into packets.
+struct NetworkIO {
+    address: usize,
+    addressed: bool,
+    bus: Rc<RefCell<Bus>>,
+    pending: Vec<Value>,
+}
+
+impl AsyncIO for NetworkIO {
+    fn poll_input(&mut self) -> Poll<Option<Value>> {
+        if !self.addressed {
+            self.addressed = true;
+            return Poll::Ready(Some(Value::try_from(self.address).unwrap()));
+        }
+
+        let mut bus = self.bus.borrow_mut();
+        let value = bus.queues[self.address].pop_front();
+
+        bus.empty_reads[self.address] = match value {
+            Some(_) => 0,
+            None => bus.empty_reads[self.address] + 1,
+        };
+
+        Poll::Ready(Some(value.unwrap_or(NO_PACKET)))
+    }
+
+    fn output(&mut self, value: Value) {
+        self.pending.push(value);
+
+        if let [destination, x, y] = self.pending[..] {
+            let mut bus = self.bus.borrow_mut();
+            bus.outbox.push(Packet { destination, x, y });
+            bus.empty_reads[self.address] = 0;
+
+            self.pending.clear();
+        }
+    }
+}
+
+type Hook<'n> = Box<dyn FnMut(NetworkEvent) -> Response + 'n>;
+
+// Machines running the same program, each with its own address (starting at zero) and packet queue.
+// Packets sent to any other address are handed to the hook registered for it.
+pub struct Network<'n> {
+    scheduler: Scheduler<'n>,
+    bus: Rc<RefCell<Bus>>,
+    hooks: HashMap<Value, Hook<'n>>,
+}
+
+impl<'n> Network<'n> {
+    pub fn new(program: &[Value], size: usize) -> Network<'n> {
+        let bus = Rc::new(RefCell::new(Bus {
+            queues: vec![VecDeque::new(); size],
+            outbox: Vec::new(),
+            empty_reads: vec![0; size],
+        }));
+
+        let mut scheduler = Scheduler::new();
+        for address in 0..size {
+            scheduler.add(
+                Computer::new(program.to_vec(), None),
+                Box::new(NetworkIO {
+                    address,
+                    addressed: false,
+                    bus: bus.clone(),
+                    pending: Vec::new(),
+                }),
+            );
+        }
+
+        Network {
+            scheduler,
+            bus,
+            hooks: HashMap::new(),
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.bus.borrow().queues.len()
+    }
+
+    // Hooks are called with all packets sent to their address, and whenever the network is idle.
+    // The packets they respond with are sent as if by a machine.
+    pub fn add_hook<H>(&mut self, address: Value, hook: H)
+    where
+        H: FnMut(NetworkEvent) -> Response + 'n,
+    {
+        self.hooks.insert(address, Box::new(hook));
+    }
+
+    // Limits the number of instructions (across all machines) executed by each call to run, so that
+    // a network no hook ever stops fails instead of running forever
+    pub fn set_step_budget(&mut self, step_budget: Option<usize>) {
+        self.scheduler.set_step_budget(step_budget);
+    }
+
+    pub fn send(&mut self, packet: Packet) {
+        self.bus.borrow_mut().outbox.push(packet);
+    }
+
+    // Runs until a hook stops the network or all machines halt. Fails if the step budget runs out.
+    pub fn run(&mut self) -> Result<(), NetworkError> {
+        self.scheduler.reset_steps();
+
+        loop {
+            if self.route()? {
+                return Ok(());
+            }
+
+            if self.is_idle() && self.notify(NetworkEvent::Idle)? {
+                return Ok(());
+            }
+
+            if (0..self.size()).all(|machine| self.scheduler.status(machine) == Status::Halted) {
+                return Ok(());
+            }
+
+            self.scheduler
+                .run_round()
+                .and_then(|_| self.scheduler.check_step_budget())
+                .map_err(NetworkError::Scheduler)?;
+        }
+    }
+
+    // Halted machines never read again, so only the others need to be waiting
+    fn is_idle(&self) -> bool {
+        let bus = self.bus.borrow();
+
+        bus.outbox.is_empty()
+            && (0..self.size())
+                .filter(|&machine| self.scheduler.status(machine) != Status::Halted)
+                .all(|machine| {
+                    bus.queues[machine].is_empty() && bus.empty_reads[machine] >= IDLE_READS
+                })
+    }
+
+    // Delivers all sent packets, returning whether a hook asked to stop
+    fn route(&mut self) -> Result<bool, NetworkError> {
+        loop {
+            let outbox: Vec<_> = self.bus.borrow_mut().outbox.drain(..).collect();
+            if outbox.is_empty() {
+                return Ok(false);
+            }
+
+            for packet in outbox {
+                let machine = usize::try_from(packet.destination)
+                    .ok()
+                    .filter(|&address| address < self.size());
+
+                if let Some(address) = machine {
+                    let mut bus = self.bus.borrow_mut();
+                    bus.queues[address].push_back(packet.x);
+                    bus.queues[address].push_back(packet.y);
+                } else if !self.hooks.contains_key(&packet.destination) {
+                    return Err(NetworkError::UnknownDestination(packet));
+                } else if self.respond(packet.destination, NetworkEvent::Packet(packet)) {
+                    return Ok(true);
+                }
+            }
+        }
+    }
+
+    // Notifies all hooks of an event, returning whether any of them asked to stop
+    fn notify(&mut self, event: NetworkEvent) -> Result<bool, NetworkError> {
+        let mut addresses: Vec<_> = self.hooks.keys().copied().collect();
+        addresses.sort_unstable();
+
+        for address in addresses {
+            if self.respond(address, event) {
+                return Ok(true);
+            }
+        }
+
+        self.route()
+    }
+
+    fn respond(&mut self, address: Value, event: NetworkEvent) -> bool {
+        match (self.hooks.get_mut(&address).unwrap())(event) {
+            Response::Continue => false,
+            Response::Send(packets) => {
+                self.bus.borrow_mut().outbox.extend(packets);
+                false
+            }
+            Response::Stop => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod networking {
+    use super::*;
+
+    // Waits for packets, and forwards them to the next address after adding one to y
+    fn relay() -> Vec<Value> {
+        assembler::assemble(
+            "
+                  in   [address]
+            wait: in   [x]
+                  eq   [x], #-1, [empty]
+                  jt   [empty], #wait
+                  in   [y]
+                  add  [address], #1, [next]
+                  add  [y], #1, [y]
+                  out  [next]
+                  out  [x]
+                  out  [y]
+                  jt   #1, #wait
+            address: data 0
+            x:       data 0
+            y:       data 0
+            next:    data 0
+            empty:   data 0
+            ",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn hooks_receive_packets() {
+        let mut received = None;
+
+        {
+            let mut network = Network::new(&relay(), 3);
+            network.add_hook(3, |event| match event {
+                NetworkEvent::Packet(packet) => {
+                    received = Some(packet);
+                    Response::Stop
+                }
+                NetworkEvent::Idle => Response::Continue,
+            });
+
+            network.send(Packet {
+                destination: 0,
+                x: 42,
+                y: 100,
+            });
+            assert_eq!(network.run(), Ok(()));
+        }
+
+        assert_eq!(
+            received,
+            Some(Packet {
+                destination: 3,
+                x: 42,
+                y: 103
+            })
+        );
+    }
+
+    #[test]
+    fn idle_hook() {
+        // Resends the last packet it received to the first machine whenever the network is idle
+        let mut received = Vec::new();
+
+        {
+            let mut network = Network::new(&relay(), 3);
+            network.add_hook(3, |event| match event {
+                NetworkEvent::Packet(packet) => {
+                    received.push(packet.y);
+
+                    if received.len() == 3 {
+                        Response::Stop
+                    } else {
+                        Response::Continue
+                    }
+                }
+                NetworkEvent::Idle => match received.last() {
+                    Some(&y) => Response::Send(vec![Packet {
+                        destination: 0,
+                        x: 0,
+                        y,
+                    }]),
+                    None => Response::Send(vec![Packet {
+                        destination: 0,
+                        x: 0,
+                        y: 1,
+                    }]),
+                },
+            });
+
+            assert_eq!(network.run(), Ok(()));
+        }
+
+        assert_eq!(received, vec![4, 7, 10]);
+    }
+
+    #[test]
+    fn unknown_destination() {
+        let mut network = Network::new(&relay(), 2);
+        network.send(Packet {
+            destination: 0,
+            x: 0,
+            y: 0,
+        });
+
+        assert_eq!(
+            network.run(),
+            Err(NetworkError::UnknownDestination(Packet {
+                destination: 2,
+                x: 0,
+                y: 2
+            }))
+        );
+    }
+
+    #[test]
+    fn halted_machines() {
+        // Machine 1 halts right away, while machine 0 waits for packets forever
+        let program = assembler::assemble(
+            "
+                  in   [address]
+                  eq   [address], #1, [halt]
+                  jt   [halt], #stop
+            wait: in   [x]
+                  jt   #1, #wait
+            stop: hlt
+            address: data 0
+            x:       data 0
+            halt:    data 0
+            ",
+        )
+        .unwrap();
+
+        let mut network = Network::new(&program, 2);
+        network.set_step_budget(Some(100_000));
+        network.add_hook(2, |event| match event {
+            NetworkEvent::Idle => Response::Stop,
+            NetworkEvent::Packet(_) => Response::Continue,
+        });
+
+        assert_eq!(network.run(), Ok(()));
+    }
+
+    #[test]
+    fn step_budget() {
+        // Nothing is ever sent, so the network stays idle and the hook never stops it
+        let mut idle_events = 0;
+
+        let result = {
+            let mut network = Network::new(&relay(), 2);
+            network.set_step_budget(Some(10_000));
+            network.add_hook(2, |_| {
+                idle_events += 1;
+                Response::Continue
+            });
+
+            network.run()
+        };
+
+        match result {
+            Err(NetworkError::Scheduler(SchedulerError::BudgetExceeded { budget, machines })) => {
+                assert_eq!(budget, 10_000);
+                assert_eq!(machines.len(), 2);
+            }
+            result => panic!("Expected the step budget to run out, got {:?}", result),
+        }
+        assert!(idle_events > 0);
+    }
+}
@@ -0,0 +1,364 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::mem;
+
+use super::disassembler::{self, Item};
+use super::registry::Registry;
+use super::*;
+
+// Counts what a computer spends its time on. Set it on a computer before running it, and inspect
+// it once done.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Profiler {
+    steps: u64,
+    io_waits: u64,
+    memory_high_water: Address,
+    // Executions of the instruction at each address of the program image, and at the addresses
+    // past it that any were executed at
+    hits: Vec<u64>,
+    far_hits: BTreeMap<Address, u64>,
+    instructions: HashMap<Instruction, u64>,
+    // Names of the extensions that were executed, as given by the computer's registry
+    extensions: HashMap<Value, &'static str>,
+}
+
+impl Profiler {
+    pub fn new() -> Profiler {
+        Profiler::default()
+    }
+
+    // Program images take up memory even before anything is written
+    pub(crate) fn load(&mut self, image_size: usize) {
+        self.memory_high_water = self.memory_high_water.max(image_size.saturating_sub(1));
+
+        if image_size > self.hits.len() {
+            self.hits.resize(image_size, 0);
+
+            let far_hits = self.far_hits.split_off(&image_size);
+            for (address, hits) in mem::replace(&mut self.far_hits, far_hits) {
+                self.hits[address] += hits;
+            }
+        }
+    }
+
+    pub(crate) fn record(
+        &mut self,
+        program_counter: Address,
+        instruction: Instruction,
+        mnemonic: &'static str,
+        highest_write: Option<Address>,
+    ) {
+        self.steps += 1;
+        *self.instructions.entry(instruction).or_insert(0) += 1;
+
+        if let Instruction::Extension(opcode) = instruction {
+            self.extensions.insert(opcode, mnemonic);
+        }
+
+        match self.hits.get_mut(program_counter) {
+            Some(hits) => *hits += 1,
+            None => *self.far_hits.entry(program_counter).or_insert(0) += 1,
+        }
+
+        if let Some(address) = highest_write {
+            self.memory_high_water = self.memory_high_water.max(address);
+        }
+    }
+
+    pub(crate) fn io_wait(&mut self) {
+        self.io_waits += 1;
+    }
+
+    // Number of instructions executed
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    // Number of times an input instruction was reached with no input available
+    pub fn io_waits(&self) -> u64 {
+        self.io_waits
+    }
+
+    // Highest address in use, either by the program image or by a write
+    pub fn memory_high_water(&self) -> Address {
+        self.memory_high_water
+    }
+
+    pub fn hits(&self, address: Address) -> u64 {
+        match self.hits.get(address) {
+            Some(hits) => *hits,
+            None => self.far_hits.get(&address).copied().unwrap_or(0),
+        }
+    }
+
+    pub fn instruction_hits(&self, instruction: Instruction) -> u64 {
+        self.instructions.get(&instruction).copied().unwrap_or(0)
+    }
+
+    // The most executed addresses, most executed first
+    pub fn hot_spots(&self, count: usize) -> Vec<(Address, u64)> {
+        let mut hot_spots: Vec<_> = self
+            .hits
+            .iter()
+            .copied()
+            .enumerate()
+            .chain(
+                self.far_hits
+                    .iter()
+                    .map(|(&address, &hits)| (address, hits)),
+            )
+            .filter(|&(_, hits)| hits > 0)
+            .collect();
+
+        hot_spots.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        hot_spots.truncate(count);
+        hot_spots
+    }
+
+    // Totals, instructions by number of executions and the hottest addresses
+    pub fn report(&self, hot_spots: usize) -> String {
+        let mut report = String::new();
+        let share = |hits| 100.0 * hits as f64 / self.steps.max(1) as f64;
+
+        writeln!(report, "Steps: {}", self.steps).unwrap();
+        writeln!(report, "IO waits: {}", self.io_waits).unwrap();
+        writeln!(report, "Memory high-water mark: {}", self.memory_high_water).unwrap();
+
+        let mut instructions: Vec<_> = self.instructions.iter().collect();
+        instructions.sort_by(|a, b| b.1.cmp(a.1).then(a.0.opcode().cmp(&b.0.opcode())));
+
+        writeln!(report, "\nInstructions:").unwrap();
+        for (instruction, hits) in instructions {
+            writeln!(
+                report,
+                "{:>6} {:>12} {:>6.2}%",
+                self.mnemonic(*instruction),
+                hits,
+                share(*hits)
+            )
+            .unwrap();
+        }
+
+        writeln!(report, "\nHot spots:").unwrap();
+        for (address, hits) in self.hot_spots(hot_spots) {
+            writeln!(report, "{:>6} {:>12} {:>6.2}%", address, hits, share(hits)).unwrap();
+        }
+
+        report
+    }
+
+    fn mnemonic(&self, instruction: Instruction) -> &'static str {
+        match instruction {
+            Instruction::Extension(opcode) => self.extensions[&opcode],
+            _ => instruction.mnemonic(),
+        }
+    }
+
+    // Disassembly of a program with the number of times each instruction was executed next to it.
+    // Instructions that were never executed are left blank.
+    pub fn annotate(&self, program: &[Value]) -> String {
+        self.annotate_items(&disassembler::disassemble(program))
+    }
+
+    // Same as annotate, for programs that use the extensions in a registry
+    pub fn annotate_with<M: Memory>(&self, program: &[Value], registry: &Registry<M>) -> String {
+        self.annotate_items(&disassembler::disassemble_with(program, registry))
+    }
+
+    fn annotate_items(&self, items: &[Item]) -> String {
+        items
+            .iter()
+            .map(|item| match (item, self.hits(item.address())) {
+                (Item::Instruction { .. }, hits) if hits > 0 => format!("{:>12} {}\n", hits, item),
+                _ => format!("{:>12} {}\n", "", item),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod profiling {
+    use super::*;
+
+    // Counts down from 3, outputting each value
+    fn countdown() -> Vec<Value> {
+        assembler::assemble(
+            "
+            loop: out  [n]
+                  add  [n], #-1, [n]
+                  jt   [n], #loop
+                  hlt
+            n:    data 3
+            ",
+        )
+        .unwrap()
+    }
+
+    fn profile(program: Vec<Value>, inputs: &[Value]) -> Profiler {
+        let mut profiler = Profiler::new();
+
+        {
+            let mut computer = Computer::new(program, None);
+            computer.set_profiler(&mut profiler);
+
+            for &input in inputs {
+                while computer.run_until_event().unwrap() != Event::NeedsInput {}
+                computer.provide_input(input);
+            }
+            while computer.run_until_event().unwrap() != Event::Halted {}
+        }
+
+        profiler
+    }
+
+    #[test]
+    fn counts() {
+        let profiler = profile(countdown(), &[]);
+
+        assert_eq!(profiler.steps(), 10);
+        assert_eq!(profiler.hits(0), 3);
+        assert_eq!(profiler.hits(2), 3);
+        assert_eq!(profiler.hits(9), 1);
+        assert_eq!(profiler.hits(1), 0);
+        assert_eq!(profiler.instruction_hits(Instruction::JumpIfTrue), 3);
+        assert_eq!(profiler.instruction_hits(Instruction::Halt), 1);
+        assert_eq!(profiler.instruction_hits(Instruction::Input), 0);
+        assert_eq!(profiler.hot_spots(2), vec![(0, 3), (2, 3)]);
+    }
+
+    #[test]
+    fn io_waits_and_memory() {
+        // Both inputs are read once the program has stalled on them
+        let profiler = profile(vec![3, 100, 203, 50, 99], &[1, 2]);
+
+        assert_eq!(profiler.io_waits(), 2);
+        assert_eq!(profiler.steps(), 3);
+        assert_eq!(profiler.memory_high_water(), 100);
+
+        let profiler = profile(vec![104, 1, 99], &[]);
+        assert_eq!(profiler.memory_high_water(), 2);
+    }
+
+    // Always has an input ready
+    struct Ready;
+
+    impl IO for Ready {
+        fn input(&mut self) -> Option<Value> {
+            Some(1)
+        }
+
+        fn output(&mut self, _: Value) {}
+    }
+
+    #[test]
+    fn inputs_ready() {
+        let mut profiler = Profiler::new();
+
+        {
+            let mut ready = Ready;
+            let mut computer = Computer::new(vec![3, 100, 203, 50, 99], Some(&mut ready));
+            computer.set_profiler(&mut profiler);
+            computer.run().unwrap();
+        }
+
+        assert_eq!(profiler.io_waits(), 0);
+        assert_eq!(profiler.steps(), 3);
+    }
+
+    #[test]
+    fn code_past_the_image() {
+        // Writes a halt far past the image and jumps to it
+        let far = 1 << 40;
+        let profiler = profile(vec![1101, 99, 0, far, 1105, 1, far], &[]);
+
+        assert_eq!(profiler.hits(far as Address), 1);
+        assert_eq!(profiler.hits(4), 1);
+        assert_eq!(profiler.memory_high_water(), far as Address);
+        assert_eq!(
+            profiler.hot_spots(3),
+            vec![(0, 1), (4, 1), (far as Address, 1)]
+        );
+    }
+
+    #[test]
+    fn report() {
+        let profiler = profile(countdown(), &[]);
+
+        assert_eq!(
+            profiler.report(1),
+            [
+                "Steps: 10",
+                "IO waits: 0",
+                "Memory high-water mark: 10",
+                "",
+                "Instructions:",
+                "   add            3  30.00%",
+                "   out            3  30.00%",
+                "    jt            3  30.00%",
+                "   hlt            1  10.00%",
+                "",
+                "Hot spots:",
+                "     0            3  30.00%",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn annotated_disassembly() {
+        let profiler = profile(countdown(), &[]);
+
+        assert_eq!(
+            profiler.annotate(&countdown()),
+            [
+                "           3      0: out  [10]",
+                "           3      2: add  [10], #-1, [10]",
+                "           3      6: jt   [10], #0",
+                "           1      9: hlt",
+                "                 10: data 3",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    // Writes its second argument to the address given by the first
+    fn poke(arguments: &[Argument], hardware: &mut Hardware) -> Option<RegisterChange> {
+        let address = Address::from_value(arguments[0].get_input()).unwrap();
+        hardware.write(address, arguments[1].get_input());
+        None
+    }
+
+    #[test]
+    fn extensions() {
+        let mut registry: Registry = Registry::new();
+        registry
+            .register(
+                14,
+                registry::Extension {
+                    mnemonic: "poke",
+                    argument_types: &[ArgumentType::In, ArgumentType::In],
+                    exec: poke,
+                },
+            )
+            .unwrap();
+
+        let program = vec![1114, 5, 7, 99];
+        let mut profiler = Profiler::new();
+        {
+            let mut computer = Computer::new(program.clone(), None);
+            computer.set_registry(&registry);
+            computer.set_profiler(&mut profiler);
+            computer.run().unwrap();
+        }
+
+        assert!(profiler.report(0).contains("  poke            1  50.00%"));
+        // Extensions write without out-arguments
+        assert_eq!(profiler.memory_high_water(), 5);
+        assert_eq!(
+            profiler.annotate_with(&program, &registry),
+            "           1      0: poke #5, #7\n           1      3: hlt\n"
+        );
+    }
+}
@@ -0,0 +1,293 @@
+use core::convert::TryFrom;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+use super::hardware::*;
+use super::instruction::*;
+use super::memory::{Memory, PagedMemory};
+
+// Executes an extension with its resolved arguments, in the same way built-in instructions do
+pub type Exec<M = PagedMemory> = fn(&[Argument], &mut Hardware<M>) -> Option<RegisterChange>;
+
+pub struct Extension<M = PagedMemory> {
+    pub mnemonic: &'static str,
+    pub argument_types: &'static [ArgumentType],
+    pub exec: Exec<M>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RegistryError {
+    // Opcodes are the last two digits of an instruction
+    InvalidOpcode(Value),
+    BuiltinOpcode(Value),
+    DuplicateOpcode(Value),
+    TooManyArguments { opcode: Value, arguments: usize },
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use RegistryError::*;
+
+        match self {
+            InvalidOpcode(opcode) => write!(f, "{} is not a valid opcode", opcode),
+            BuiltinOpcode(opcode) => write!(f, "opcode {} is built in", opcode),
+            DuplicateOpcode(opcode) => write!(f, "opcode {} is already registered", opcode),
+            TooManyArguments { opcode, arguments } => write!(
+                f,
+                "opcode {} takes {} arguments, but at most {} are supported",
+                opcode, arguments, MAX_ARGUMENTS
+            ),
+        }
+    }
+}
+
+impl Error for RegistryError {}
+
+// Opcodes added on top of the built-in instruction set. Computers only run them once the registry
+// is set on them.
+pub struct Registry<M = PagedMemory> {
+    extensions: HashMap<Value, Extension<M>>,
+}
+
+impl<M: Memory> Default for Registry<M> {
+    fn default() -> Registry<M> {
+        Registry {
+            extensions: HashMap::new(),
+        }
+    }
+}
+
+impl<M: Memory> Registry<M> {
+    pub fn new() -> Registry<M> {
+        Registry::default()
+    }
+
+    pub fn register(
+        &mut self,
+        opcode: Value,
+        extension: Extension<M>,
+    ) -> Result<(), RegistryError> {
+        if !(0..100).contains(&opcode) {
+            return Err(RegistryError::InvalidOpcode(opcode));
+        }
+
+        if Instruction::try_from(opcode).is_ok() {
+            return Err(RegistryError::BuiltinOpcode(opcode));
+        }
+
+        if extension.argument_types.len() > MAX_ARGUMENTS {
+            return Err(RegistryError::TooManyArguments {
+                opcode,
+                arguments: extension.argument_types.len(),
+            });
+        }
+
+        if self.extensions.contains_key(&opcode) {
+            return Err(RegistryError::DuplicateOpcode(opcode));
+        }
+
+        self.extensions.insert(opcode, extension);
+        Ok(())
+    }
+
+    pub fn get(&self, opcode: Value) -> Option<&Extension<M>> {
+        self.extensions.get(&opcode)
+    }
+
+    // Name the extension is listed and assembled with
+    pub fn mnemonic(&self, opcode: Value) -> Option<&'static str> {
+        self.get(opcode).map(|extension| extension.mnemonic)
+    }
+
+    pub fn from_mnemonic(&self, mnemonic: &str) -> Option<Value> {
+        self.extensions
+            .iter()
+            .find(|(_, extension)| extension.mnemonic == mnemonic)
+            .map(|(opcode, _)| *opcode)
+    }
+
+    // Decodes the raw value of an instruction with a registered opcode
+    pub fn decode(&self, value: Value) -> Option<Decoded> {
+        self.get(value % 100)
+            .map(|extension| Decoded::extension(value, extension.argument_types))
+    }
+}
+
+#[cfg(test)]
+mod registering {
+    use super::*;
+    use crate::Computer;
+    use crate::IntcodeError;
+    use ArgumentType::*;
+
+    fn div(arguments: &[Argument], hardware: &mut Hardware) -> Option<RegisterChange> {
+        let (lhs, rhs) = (arguments[0].get_input(), arguments[1].get_input());
+        hardware.write(arguments[2].get_output(), lhs / rhs);
+        None
+    }
+
+    fn modulo(arguments: &[Argument], hardware: &mut Hardware) -> Option<RegisterChange> {
+        let (lhs, rhs) = (arguments[0].get_input(), arguments[1].get_input());
+        hardware.write(arguments[2].get_output(), lhs % rhs);
+        None
+    }
+
+    // Outputs its argument twice
+    fn echo(arguments: &[Argument], hardware: &mut Hardware) -> Option<RegisterChange> {
+        hardware.output(arguments[0].get_input());
+        hardware.output(arguments[0].get_input());
+        None
+    }
+
+    // Unconditional jump
+    fn jump(arguments: &[Argument], _: &mut Hardware) -> Option<RegisterChange> {
+        Some(RegisterChange::ProgramCounter {
+            new_value: arguments[0].get_input(),
+        })
+    }
+
+    // Writes its second argument to the address given by its first, without an out-argument
+    fn poke(arguments: &[Argument], hardware: &mut Hardware) -> Option<RegisterChange> {
+        let address = Address::from_value(arguments[0].get_input()).unwrap();
+        hardware.write(address, arguments[1].get_input());
+        None
+    }
+
+    fn registry() -> Registry {
+        let mut registry = Registry::new();
+
+        let extensions = vec![
+            (10, "div", &[In, In, Out][..], div as Exec),
+            (11, "mod", &[In, In, Out], modulo),
+            (12, "echo", &[In], echo),
+            (13, "jmp", &[In], jump),
+            (14, "poke", &[In, In], poke),
+        ];
+
+        for (opcode, mnemonic, argument_types, exec) in extensions {
+            registry
+                .register(
+                    opcode,
+                    Extension {
+                        mnemonic,
+                        argument_types,
+                        exec,
+                    },
+                )
+                .unwrap();
+        }
+
+        registry
+    }
+
+    fn outputs(program: Vec<Value>, registry: &Registry) -> Result<Vec<Value>, IntcodeError> {
+        let mut computer = Computer::new(program, None);
+        computer.set_registry(registry);
+
+        let mut outputs = Vec::new();
+        loop {
+            match computer.run_until_event()? {
+                crate::Event::Output(value) => outputs.push(value),
+                _ => break Ok(outputs),
+            }
+        }
+    }
+
+    #[test]
+    fn arithmetic() {
+        // 17 / 5 and 17 % 5, with the first argument in indexed mode
+        let program = vec![
+            1010, 13, 5, 14, 1011, 13, 5, 15, 12, 14, 4, 15, 99, 17, 0, 0,
+        ];
+        let mut computer = Computer::new(program.clone(), None);
+        let registry = registry();
+        computer.set_registry(&registry);
+
+        assert_eq!(computer.step(), Ok(Instruction::Extension(10)));
+        assert_eq!(outputs(program, &registry), Ok(vec![3, 3, 2]));
+    }
+
+    #[test]
+    fn output_and_jumps() {
+        // Skips over an unknown opcode
+        let program = vec![113, 4, 42, 42, 112, 7, 99];
+
+        assert_eq!(outputs(program, &registry()), Ok(vec![7, 7]));
+    }
+
+    #[test]
+    fn outputs_before_input() {
+        // Echoes 7 and then waits for input: both outputs come before the wait
+        let mut computer = Computer::new(vec![1112, 7, 3, 0, 99], None);
+        let registry = registry();
+        computer.set_registry(&registry);
+
+        assert_eq!(computer.run_until_event(), Ok(crate::Event::Output(7)));
+        assert_eq!(computer.run_until_event(), Ok(crate::Event::Output(7)));
+        assert_eq!(computer.run_until_event(), Ok(crate::Event::NeedsInput));
+    }
+
+    #[test]
+    fn self_modification() {
+        // Outputs, overwrites the output with a halt and jumps back to it
+        let program = vec![104, 1, 1114, 0, 99, 1105, 1, 0];
+        let registry = registry();
+        let mut computer = Computer::new(program, None);
+        computer.set_registry(&registry);
+
+        assert_eq!(computer.step(), Ok(Instruction::Output));
+        assert_eq!(computer.step(), Ok(Instruction::Extension(14)));
+        assert_eq!(computer.step(), Ok(Instruction::JumpIfTrue));
+        assert_eq!(computer.step(), Ok(Instruction::Halt));
+    }
+
+    #[test]
+    fn unregistered_opcodes() {
+        assert_eq!(
+            outputs(vec![1110, 1, 2, 0, 99], &Registry::new()),
+            Err(IntcodeError::UnknownOpcode {
+                program_counter: 0,
+                instruction: 1110
+            })
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let extension = || Extension {
+            mnemonic: "nop",
+            argument_types: &[],
+            exec: |_, _| None,
+        };
+        let mut registry: Registry = Registry::new();
+
+        assert_eq!(
+            registry.register(100, extension()),
+            Err(RegistryError::InvalidOpcode(100))
+        );
+        assert_eq!(
+            registry.register(99, extension()),
+            Err(RegistryError::BuiltinOpcode(99))
+        );
+        assert_eq!(registry.register(50, extension()), Ok(()));
+        assert_eq!(
+            registry.register(50, extension()),
+            Err(RegistryError::DuplicateOpcode(50))
+        );
+        assert_eq!(
+            registry.register(
+                51,
+                Extension {
+                    mnemonic: "many",
+                    argument_types: &[ArgumentType::In; 4],
+                    exec: |_, _| None,
+                }
+            ),
+            Err(RegistryError::TooManyArguments {
+                opcode: 51,
+                arguments: 4
+            })
+        );
+    }
+}
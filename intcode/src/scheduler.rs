@@ -0,0 +1,526 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use std::rc::Rc;
+use std::task::Poll;
+
+use super::snapshot::Snapshot;
+use super::*;
+
+// Maximum number of instructions a machine executes before yielding to the next one, so that
+// machines that never wait on input do not starve the rest
+const TIME_SLICE: usize = 1000;
+
+// Non-blocking counterpart to IO: when no input is available yet the machine yields, and is polled
+// again on its next turn. Ready(None) means input has ended for good.
+pub trait AsyncIO {
+    fn poll_input(&mut self) -> Poll<Option<Value>>;
+    fn output(&mut self, value: Value);
+}
+
+// Queue of values shared between machines run by the same scheduler
+#[derive(Debug, Clone, Default)]
+pub struct Channel(Rc<RefCell<VecDeque<Value>>>);
+
+impl Channel {
+    pub fn new() -> Channel {
+        Channel::default()
+    }
+
+    pub fn send(&self, value: Value) {
+        self.0.borrow_mut().push_back(value);
+    }
+
+    pub fn receive(&self) -> Option<Value> {
+        self.0.borrow_mut().pop_front()
+    }
+
+    pub fn clear(&self) {
+        self.0.borrow_mut().clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.borrow().is_empty()
+    }
+
+    // All values sent but not yet received, oldest first
+    pub fn values(&self) -> Vec<Value> {
+        self.0.borrow().iter().copied().collect()
+    }
+}
+
+// Reads from a channel, and sends each output to all of the output channels
+pub struct ChannelIO {
+    input: Channel,
+    outputs: Vec<Channel>,
+}
+
+impl ChannelIO {
+    pub fn new(input: Channel, outputs: Vec<Channel>) -> ChannelIO {
+        ChannelIO { input, outputs }
+    }
+}
+
+impl AsyncIO for ChannelIO {
+    fn poll_input(&mut self) -> Poll<Option<Value>> {
+        match self.input.receive() {
+            Some(value) => Poll::Ready(Some(value)),
+            None => Poll::Pending,
+        }
+    }
+
+    fn output(&mut self, value: Value) {
+        for output in &self.outputs {
+            output.send(value);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Status {
+    Running,
+    Blocked,
+    Halted,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Status::Running => write!(f, "running"),
+            Status::Blocked => write!(f, "blocked on input"),
+            Status::Halted => write!(f, "halted"),
+        }
+    }
+}
+
+// Where a machine was, and what it was doing, when the scheduler gave up on it
+#[derive(Debug, Clone, PartialEq)]
+pub struct MachineState {
+    pub machine: usize,
+    pub program_counter: Address,
+    pub status: Status,
+}
+
+impl fmt::Display for MachineState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "machine {} {} at address {}",
+            self.machine, self.status, self.program_counter
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SchedulerError {
+    // A machine (identified by the index returned when it was added) failed
+    Machine {
+        machine: usize,
+        error: IntcodeError,
+    },
+    // No machine can make progress, but not all of them have halted
+    Deadlock {
+        machines: Vec<MachineState>,
+    },
+    // More instructions than allowed were executed without all machines halting, which usually
+    // means some of them are stuck in a loop
+    BudgetExceeded {
+        budget: usize,
+        machines: Vec<MachineState>,
+    },
+}
+
+impl fmt::Display for SchedulerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (description, machines) = match self {
+            SchedulerError::Machine { machine, error } => {
+                return write!(f, "machine {} failed: {}", machine, error)
+            }
+            SchedulerError::Deadlock { machines } => ("deadlock".to_string(), machines),
+            SchedulerError::BudgetExceeded { budget, machines } => {
+                (format!("step budget of {} exceeded", budget), machines)
+            }
+        };
+
+        let machines: Vec<_> = machines.iter().map(MachineState::to_string).collect();
+        write!(f, "{} ({})", description, machines.join(", "))
+    }
+}
+
+impl Error for SchedulerError {}
+
+struct Machine<'hw> {
+    computer: Computer<'hw>,
+    io: Box<dyn AsyncIO + 'hw>,
+    status: Status,
+}
+
+impl<'hw> Machine<'hw> {
+    // Runs the machine until it blocks, halts or uses up its time slice, returning how many
+    // instructions it executed
+    fn turn(&mut self) -> Result<usize, IntcodeError> {
+        let mut steps = 0;
+
+        for _ in 0..TIME_SLICE {
+            match self.computer.step() {
+                Err(error @ IntcodeError::InputExhausted { .. }) => match self.io.poll_input() {
+                    Poll::Ready(Some(value)) => self.computer.provide_input(value),
+                    Poll::Ready(None) => return Err(error),
+                    Poll::Pending => {
+                        // Machines stay blocked over several turns, but only wait once
+                        if self.status != Status::Blocked {
+                            self.computer.io_wait();
+                        }
+
+                        self.status = Status::Blocked;
+                        return Ok(steps);
+                    }
+                },
+                Err(error) => return Err(error),
+                Ok(instruction) => {
+                    steps += 1;
+                    self.status = Status::Running;
+
+                    while let Some(value) = self.computer.hardware.pop_output() {
+                        self.io.output(value);
+                    }
+
+                    if instruction == Instruction::Halt {
+                        self.status = Status::Halted;
+                        return Ok(steps);
+                    }
+                }
+            }
+        }
+
+        Ok(steps)
+    }
+}
+
+// Runs any number of machines cooperatively on a single thread, taking turns in the order in which
+// they were added
+#[derive(Default)]
+pub struct Scheduler<'hw> {
+    machines: Vec<Machine<'hw>>,
+    step_budget: Option<usize>,
+    // Instructions executed during the current run
+    steps: usize,
+}
+
+impl<'hw> Scheduler<'hw> {
+    pub fn new() -> Scheduler<'hw> {
+        Scheduler::default()
+    }
+
+    // Returns the index that identifies the machine. The computer's own IO is not used.
+    pub fn add(&mut self, computer: Computer<'hw>, io: Box<dyn AsyncIO + 'hw>) -> usize {
+        self.machines.push(Machine {
+            computer,
+            io,
+            status: Status::Running,
+        });
+
+        self.machines.len() - 1
+    }
+
+    // Limits the number of instructions (across all machines) executed by each call to run
+    pub fn set_step_budget(&mut self, step_budget: Option<usize>) {
+        self.step_budget = step_budget;
+    }
+
+    pub fn computer(&self, machine: usize) -> &Computer<'hw> {
+        &self.machines[machine].computer
+    }
+
+    pub fn status(&self, machine: usize) -> Status {
+        self.machines[machine].status
+    }
+
+    // Returns a machine to a previously captured state, so that it can be reused
+    pub fn restore(&mut self, machine: usize, snapshot: &Snapshot) {
+        let machine = &mut self.machines[machine];
+
+        machine.computer.restore(snapshot);
+        machine.status = Status::Running;
+    }
+
+    // Gives a turn to every machine that has not halted, returning whether any of them made
+    // progress
+    pub fn run_round(&mut self) -> Result<bool, SchedulerError> {
+        let mut steps = 0;
+
+        for (index, machine) in self.machines.iter_mut().enumerate() {
+            if machine.status != Status::Halted {
+                steps += machine.turn().map_err(|error| SchedulerError::Machine {
+                    machine: index,
+                    error,
+                })?;
+            }
+        }
+
+        self.steps += steps;
+        Ok(steps > 0)
+    }
+
+    pub fn machine_states(&self) -> Vec<MachineState> {
+        self.machines
+            .iter()
+            .enumerate()
+            .map(|(index, machine)| MachineState {
+                machine: index,
+                program_counter: machine.computer.program_counter(),
+                status: machine.status,
+            })
+            .collect()
+    }
+
+    // Runs until all machines halt. Fails if they all get stuck waiting on input, or if the step
+    // budget runs out.
+    pub fn run(&mut self) -> Result<(), SchedulerError> {
+        self.reset_steps();
+
+        while self
+            .machines
+            .iter()
+            .any(|machine| machine.status != Status::Halted)
+        {
+            if !self.run_round()? {
+                return Err(SchedulerError::Deadlock {
+                    machines: self.machine_states(),
+                });
+            }
+
+            self.check_step_budget()?;
+        }
+
+        Ok(())
+    }
+
+    // Starts counting steps against the budget from zero, for drivers that run rounds themselves
+    pub(crate) fn reset_steps(&mut self) {
+        self.steps = 0;
+    }
+
+    // Fails if more instructions than the budget allows have run since the steps were reset
+    pub(crate) fn check_step_budget(&self) -> Result<(), SchedulerError> {
+        match self.step_budget {
+            Some(budget) if self.steps > budget => Err(SchedulerError::BudgetExceeded {
+                budget,
+                machines: self.machine_states(),
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod scheduling {
+    use super::*;
+    use crate::profiler::Profiler;
+
+    // Outputs its input plus one
+    fn increment() -> Vec<Value> {
+        vec![3, 9, 1001, 9, 1, 9, 4, 9, 99, 0]
+    }
+
+    #[test]
+    fn chain_of_machines() {
+        let channels: Vec<_> = (0..=60).map(|_| Channel::new()).collect();
+        let mut scheduler = Scheduler::new();
+
+        // Added last to first, so that most machines have to wait for their input
+        for index in (0..60).rev() {
+            scheduler.add(
+                Computer::new(increment(), None),
+                Box::new(ChannelIO::new(
+                    channels[index].clone(),
+                    vec![channels[index + 1].clone()],
+                )),
+            );
+        }
+
+        channels[0].send(0);
+        assert_eq!(scheduler.run(), Ok(()));
+        assert_eq!(channels[60].values(), vec![60]);
+        assert_eq!(scheduler.status(0), Status::Halted);
+    }
+
+    #[test]
+    fn io_waits() {
+        let (input, middle, output) = (Channel::new(), Channel::new(), Channel::new());
+        let (mut first, mut second) = (Profiler::new(), Profiler::new());
+
+        {
+            let mut scheduler = Scheduler::new();
+
+            // Added second to first, so that the second machine has to wait for its input
+            let mut computer = Computer::new(increment(), None);
+            computer.set_profiler(&mut second);
+            scheduler.add(
+                computer,
+                Box::new(ChannelIO::new(middle.clone(), vec![output.clone()])),
+            );
+
+            let mut computer = Computer::new(increment(), None);
+            computer.set_profiler(&mut first);
+            scheduler.add(
+                computer,
+                Box::new(ChannelIO::new(input.clone(), vec![middle.clone()])),
+            );
+
+            input.send(0);
+            assert_eq!(scheduler.run(), Ok(()));
+        }
+
+        assert_eq!(output.values(), vec![2]);
+        assert_eq!(first.io_waits(), 0);
+        assert_eq!(second.io_waits(), 1);
+    }
+
+    #[test]
+    fn reuse_machines() {
+        let (input, output) = (Channel::new(), Channel::new());
+        let computer = Computer::new(increment(), None);
+        let initial = computer.snapshot();
+
+        let mut scheduler = Scheduler::new();
+        let machine = scheduler.add(
+            computer,
+            Box::new(ChannelIO::new(input.clone(), vec![output.clone()])),
+        );
+
+        for value in 0..3 {
+            scheduler.restore(machine, &initial);
+            input.send(value);
+
+            assert_eq!(scheduler.run(), Ok(()));
+        }
+
+        assert_eq!(output.values(), vec![1, 2, 3]);
+        output.clear();
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn deadlock() {
+        let (a, b) = (Channel::new(), Channel::new());
+        let mut scheduler = Scheduler::new();
+
+        scheduler.add(
+            Computer::new(increment(), None),
+            Box::new(ChannelIO::new(a.clone(), vec![b.clone()])),
+        );
+        scheduler.add(
+            Computer::new(increment(), None),
+            Box::new(ChannelIO::new(b, vec![a])),
+        );
+
+        assert_eq!(
+            scheduler.run(),
+            Err(SchedulerError::Deadlock {
+                machines: vec![
+                    MachineState {
+                        machine: 0,
+                        program_counter: 0,
+                        status: Status::Blocked
+                    },
+                    MachineState {
+                        machine: 1,
+                        program_counter: 0,
+                        status: Status::Blocked
+                    },
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn step_budget() {
+        // Loops forever while the other machine waits for it
+        let busy = vec![1105, 1, 0];
+        let mut scheduler = Scheduler::new();
+        scheduler.set_step_budget(Some(5000));
+
+        scheduler.add(
+            Computer::new(busy, None),
+            Box::new(ChannelIO::new(Channel::new(), vec![])),
+        );
+        scheduler.add(
+            Computer::new(vec![104, 1, 3, 0, 99], None),
+            Box::new(ChannelIO::new(Channel::new(), vec![])),
+        );
+
+        let error = scheduler.run().unwrap_err();
+        assert_eq!(
+            error,
+            SchedulerError::BudgetExceeded {
+                budget: 5000,
+                machines: vec![
+                    MachineState {
+                        machine: 0,
+                        program_counter: 0,
+                        status: Status::Running
+                    },
+                    MachineState {
+                        machine: 1,
+                        program_counter: 2,
+                        status: Status::Blocked
+                    },
+                ]
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "step budget of 5000 exceeded (machine 0 running at address 0, \
+             machine 1 blocked on input at address 2)"
+        );
+    }
+
+    #[test]
+    fn machine_error() {
+        let mut scheduler = Scheduler::new();
+
+        scheduler.add(
+            Computer::new(vec![99], None),
+            Box::new(ChannelIO::new(Channel::new(), vec![])),
+        );
+        scheduler.add(
+            Computer::new(vec![42], None),
+            Box::new(ChannelIO::new(Channel::new(), vec![])),
+        );
+
+        assert_eq!(
+            scheduler.run(),
+            Err(SchedulerError::Machine {
+                machine: 1,
+                error: IntcodeError::UnknownOpcode {
+                    program_counter: 0,
+                    instruction: 42
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn busy_machines_yield() {
+        // Loops forever without ever reading input
+        let busy = vec![1105, 1, 0];
+        let output = Channel::new();
+        let mut scheduler = Scheduler::new();
+
+        scheduler.add(
+            Computer::new(busy, None),
+            Box::new(ChannelIO::new(Channel::new(), vec![])),
+        );
+        scheduler.add(
+            Computer::new(vec![104, 7, 99], None),
+            Box::new(ChannelIO::new(Channel::new(), vec![output.clone()])),
+        );
+
+        assert_eq!(scheduler.run_round(), Ok(true));
+        assert_eq!(scheduler.status(0), Status::Running);
+        assert_eq!(scheduler.status(1), Status::Halted);
+        assert_eq!(output.values(), vec![7]);
+    }
+}
@@ -0,0 +1,106 @@
+use serde::{Deserialize, Serialize};
+
+use super::hardware::*;
+use super::memory::PagedMemory;
+
+// The state of a computer at a point in time, as returned by Computer::snapshot. It can be
+// serialized to persist a machine and resume it later.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot<M = PagedMemory> {
+    pub(crate) hardware: Hardware<M>,
+    pub(crate) program_counter: Address,
+    pub(crate) relative_base: Address,
+    // Size of the program image the machine was started with
+    #[serde(default)]
+    pub(crate) image_size: usize,
+}
+
+#[cfg(test)]
+mod snapshots {
+    use super::super::*;
+
+    // Adds each input to a running total, which it outputs
+    fn accumulator() -> Vec<Value> {
+        vec![3, 20, 1, 20, 21, 21, 4, 21, 1105, 1, 0]
+    }
+
+    #[test]
+    fn restore() {
+        let mut computer = Computer::new(accumulator(), None);
+        computer.provide_input(5);
+        assert_eq!(computer.run_until_event(), Ok(Event::Output(5)));
+
+        let snapshot = computer.snapshot();
+
+        computer.provide_input(1);
+        assert_eq!(computer.run_until_event(), Ok(Event::Output(6)));
+
+        computer.restore(&snapshot);
+        computer.provide_input(2);
+        assert_eq!(computer.run_until_event(), Ok(Event::Output(7)));
+    }
+
+    #[test]
+    fn fork() {
+        let mut computer = Computer::new(accumulator(), None);
+        computer.provide_input(10);
+        assert_eq!(computer.run_until_event(), Ok(Event::Output(10)));
+
+        let mut fork = Computer::new(vec![], None);
+        fork.restore(&computer.snapshot());
+
+        computer.provide_input(1);
+        fork.provide_input(-1);
+        assert_eq!(computer.run_until_event(), Ok(Event::Output(11)));
+        assert_eq!(fork.run_until_event(), Ok(Event::Output(9)));
+
+        // The fork decodes the restored image as the original computer would
+        assert_eq!(fork.decoded.len(), accumulator().len());
+        assert!(fork.decoded[0].is_some());
+    }
+
+    #[test]
+    fn pending_io_and_registers() {
+        // Moves the relative base and then waits for two inputs
+        let mut computer = Computer::new(vec![109, 7, 3, 0, 3, 1, 99], None);
+        computer.provide_input(42);
+        assert_eq!(computer.run_until_event(), Ok(Event::NeedsInput));
+
+        let mut fork = Computer::new(vec![], None);
+        fork.restore(&computer.snapshot());
+        assert_eq!(fork.program_counter(), 4);
+        assert_eq!(fork.relative_base(), 7);
+
+        fork.provide_input(43);
+        assert_eq!(fork.run_until_event(), Ok(Event::Halted));
+        assert_eq!(fork.snapshot().hardware.read(0), 42);
+        assert_eq!(fork.snapshot().hardware.read(1), 43);
+    }
+
+    #[test]
+    fn restore_other_program() {
+        // Instructions decoded before restoring must not be reused afterwards
+        let mut computer = Computer::new(vec![1101, 3, 4, 7, 4, 7, 99, 0], None);
+        assert_eq!(computer.run_until_event(), Ok(Event::Output(7)));
+
+        let other = Computer::new(vec![1102, 3, 4, 7, 4, 7, 99, 0], None);
+        computer.restore(&other.snapshot());
+        assert_eq!(computer.run_until_event(), Ok(Event::Output(12)));
+    }
+
+    #[test]
+    fn serialization() {
+        let mut computer = Computer::new(accumulator(), None);
+        computer.provide_input(3);
+        computer.run_until_event().unwrap();
+
+        let json = serde_json::to_string(&computer.snapshot()).unwrap();
+        let snapshot: Snapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(snapshot, computer.snapshot());
+
+        let mut restored = Computer::new(vec![], None);
+        restored.restore(&snapshot);
+        restored.provide_input(4);
+        assert_eq!(restored.run_until_event(), Ok(Event::Output(7)));
+    }
+}
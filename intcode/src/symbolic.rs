@@ -0,0 +1,861 @@
+use core::convert::TryFrom;
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use super::*;
+
+// Longest a symbolic run or a single brute force attempt may go on for, since programs may loop
+// forever for some inputs
+const STEP_LIMIT: usize = 1_000_000;
+
+// Brute force attempts are handed out to workers in chunks of this many
+const CHUNK: usize = 64;
+
+// A value that is not known when the program is analyzed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Symbol {
+    // Initial contents of a memory cell
+    Cell(Address),
+    // The nth value read by an input instruction, starting at zero
+    Input(usize),
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Symbol::Cell(address) => write!(f, "[{}]", address),
+            Symbol::Input(nth) => write!(f, "in{}", nth),
+        }
+    }
+}
+
+// Value computed by a program in terms of symbols. Constant parts are folded as expressions are
+// built, so fully known values are always a single constant unless computing them overflows.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Const(Value),
+    Symbol(Symbol),
+    Add(Rc<Expr>, Rc<Expr>),
+    Mul(Rc<Expr>, Rc<Expr>),
+    LessThan(Rc<Expr>, Rc<Expr>),
+    Equals(Rc<Expr>, Rc<Expr>),
+    // Read from an address that depends on symbols
+    Load(Rc<Expr>),
+}
+
+impl Expr {
+    fn add(lhs: Rc<Expr>, rhs: Rc<Expr>) -> Rc<Expr> {
+        match (&*lhs, &*rhs) {
+            (Expr::Const(lhs), Expr::Const(rhs)) if lhs.checked_add(*rhs).is_some() => {
+                Rc::new(Expr::Const(lhs + rhs))
+            }
+            (Expr::Const(0), _) => rhs,
+            (_, Expr::Const(0)) => lhs,
+            _ => Rc::new(Expr::Add(lhs, rhs)),
+        }
+    }
+
+    fn mul(lhs: Rc<Expr>, rhs: Rc<Expr>) -> Rc<Expr> {
+        match (&*lhs, &*rhs) {
+            (Expr::Const(lhs), Expr::Const(rhs)) if lhs.checked_mul(*rhs).is_some() => {
+                Rc::new(Expr::Const(lhs * rhs))
+            }
+            (Expr::Const(0), _) | (_, Expr::Const(0)) => Rc::new(Expr::Const(0)),
+            (Expr::Const(1), _) => rhs,
+            (_, Expr::Const(1)) => lhs,
+            _ => Rc::new(Expr::Mul(lhs, rhs)),
+        }
+    }
+
+    fn less_than(lhs: Rc<Expr>, rhs: Rc<Expr>) -> Rc<Expr> {
+        match (&*lhs, &*rhs) {
+            (Expr::Const(lhs), Expr::Const(rhs)) => Rc::new(Expr::Const((lhs < rhs) as Value)),
+            _ => Rc::new(Expr::LessThan(lhs, rhs)),
+        }
+    }
+
+    fn equals(lhs: Rc<Expr>, rhs: Rc<Expr>) -> Rc<Expr> {
+        match (&*lhs, &*rhs) {
+            (Expr::Const(lhs), Expr::Const(rhs)) => Rc::new(Expr::Const((lhs == rhs) as Value)),
+            _ => Rc::new(Expr::Equals(lhs, rhs)),
+        }
+    }
+
+    pub fn constant(&self) -> Option<Value> {
+        match self {
+            Expr::Const(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    // Rewrites the expression as a constant plus a weighted sum of symbols, if possible. Only sums
+    // and products where one side is a constant can be rewritten, and only if the constant and
+    // coefficients do not overflow.
+    pub fn affine(&self) -> Option<Affine> {
+        match self {
+            Expr::Const(value) => Some(Affine {
+                constant: *value,
+                coefficients: BTreeMap::new(),
+            }),
+            Expr::Symbol(symbol) => Some(Affine {
+                constant: 0,
+                coefficients: vec![(*symbol, 1)].into_iter().collect(),
+            }),
+            Expr::Add(lhs, rhs) => {
+                let (mut lhs, rhs) = (lhs.affine()?, rhs.affine()?);
+
+                lhs.constant = lhs.constant.checked_add(rhs.constant)?;
+                for (symbol, coefficient) in rhs.coefficients {
+                    let sum = lhs.coefficients.entry(symbol).or_insert(0);
+                    *sum = sum.checked_add(coefficient)?;
+                }
+                lhs.coefficients.retain(|_, coefficient| *coefficient != 0);
+
+                Some(lhs)
+            }
+            Expr::Mul(lhs, rhs) => {
+                let (factor, other) = match (lhs.constant(), rhs.constant()) {
+                    (Some(factor), _) => (factor, rhs),
+                    (_, Some(factor)) => (factor, lhs),
+                    _ => return None,
+                };
+                let mut other = other.affine()?;
+
+                other.constant = other.constant.checked_mul(factor)?;
+                for coefficient in other.coefficients.values_mut() {
+                    *coefficient = coefficient.checked_mul(factor)?;
+                }
+
+                Some(other)
+            }
+            Expr::LessThan(..) | Expr::Equals(..) | Expr::Load(..) => None,
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expr::Const(value) => write!(f, "{}", value),
+            Expr::Symbol(symbol) => write!(f, "{}", symbol),
+            Expr::Add(lhs, rhs) => write!(f, "({} + {})", lhs, rhs),
+            Expr::Mul(lhs, rhs) => write!(f, "({} * {})", lhs, rhs),
+            Expr::LessThan(lhs, rhs) => write!(f, "({} < {})", lhs, rhs),
+            Expr::Equals(lhs, rhs) => write!(f, "({} == {})", lhs, rhs),
+            Expr::Load(address) => write!(f, "[{}]", address),
+        }
+    }
+}
+
+// constant + sum(coefficient * symbol), with no zero coefficients
+#[derive(Debug, Clone, PartialEq)]
+pub struct Affine {
+    pub constant: Value,
+    pub coefficients: BTreeMap<Symbol, Value>,
+}
+
+impl Affine {
+    pub fn coefficient(&self, symbol: Symbol) -> Value {
+        self.coefficients.get(&symbol).copied().unwrap_or(0)
+    }
+}
+
+// Ways in which symbolic execution can get stuck. Most of them mean that the path taken by the
+// program depends on the symbols.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SymbolicError {
+    // The program fails regardless of the symbols' values
+    Intcode(IntcodeError),
+    SymbolicInstruction { program_counter: Address },
+    SymbolicWrite { program_counter: Address },
+    SymbolicJump { program_counter: Address },
+    SymbolicRelativeBase { program_counter: Address },
+    StepLimit,
+    // An unknown (given by its index) has no values to take
+    EmptyRange { unknown: usize },
+    // There are more combinations of values than can be counted
+    TooManyCombinations,
+}
+
+impl fmt::Display for SymbolicError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use SymbolicError::*;
+
+        match self {
+            Intcode(error) => write!(f, "{}", error),
+            SymbolicInstruction { program_counter } => {
+                write!(f, "instruction at {} depends on symbols", program_counter)
+            }
+            SymbolicWrite { program_counter } => write!(
+                f,
+                "instruction at {} writes to an address that depends on symbols",
+                program_counter
+            ),
+            SymbolicJump { program_counter } => {
+                write!(f, "jump at {} depends on symbols", program_counter)
+            }
+            SymbolicRelativeBase { program_counter } => write!(
+                f,
+                "relative base change at {} depends on symbols",
+                program_counter
+            ),
+            StepLimit => write!(f, "program did not halt within {} steps", STEP_LIMIT),
+            EmptyRange { unknown } => write!(f, "unknown {} has no values to take", unknown),
+            TooManyCombinations => write!(f, "unknowns take too many combinations of values"),
+        }
+    }
+}
+
+impl Error for SymbolicError {}
+
+// Runs a program on symbolic values. Inputs are always symbols, and so are the memory cells
+// chosen before running it. Execution only goes on for as long as control flow (instructions,
+// jumps, write addresses and the relative base) does not depend on symbols.
+pub struct SymbolicMachine {
+    memory: HashMap<Address, Rc<Expr>>,
+    program_counter: Address,
+    relative_base: Address,
+    inputs: usize,
+    outputs: Vec<Rc<Expr>>,
+}
+
+impl SymbolicMachine {
+    pub fn new(program: &[Value]) -> SymbolicMachine {
+        SymbolicMachine {
+            memory: program
+                .iter()
+                .enumerate()
+                .map(|(address, &value)| (address, Rc::new(Expr::Const(value))))
+                .collect(),
+            program_counter: 0,
+            relative_base: 0,
+            inputs: 0,
+            outputs: Vec::new(),
+        }
+    }
+
+    // Replaces the initial contents of a cell with a symbol
+    pub fn make_symbolic(&mut self, address: Address) {
+        self.memory
+            .insert(address, Rc::new(Expr::Symbol(Symbol::Cell(address))));
+    }
+
+    pub fn read(&self, address: Address) -> Rc<Expr> {
+        self.memory
+            .get(&address)
+            .cloned()
+            .unwrap_or_else(|| Rc::new(Expr::Const(0)))
+    }
+
+    pub fn outputs(&self) -> &[Rc<Expr>] {
+        &self.outputs
+    }
+
+    // Runs until the program halts
+    pub fn run(&mut self) -> Result<(), SymbolicError> {
+        for _ in 0..STEP_LIMIT {
+            if !self.step()? {
+                return Ok(());
+            }
+        }
+
+        Err(SymbolicError::StepLimit)
+    }
+
+    // Concrete value of a cell that control flow depends on
+    fn concrete(&self, address: Address, error: SymbolicError) -> Result<Value, SymbolicError> {
+        self.read(address).constant().ok_or(error)
+    }
+
+    fn address(&self, value: Value, instruction: Value) -> Result<Address, SymbolicError> {
+        Address::try_from(value).map_err(|_| {
+            SymbolicError::Intcode(IntcodeError::NegativeAddress {
+                program_counter: self.program_counter,
+                instruction,
+                address: value,
+            })
+        })
+    }
+
+    // Address at an offset from the relative base
+    fn relative(&self, offset: Value, instruction: Value) -> Result<Address, SymbolicError> {
+        let address = Value::try_from(self.relative_base)
+            .ok()
+            .and_then(|relative_base| relative_base.checked_add(offset))
+            .ok_or(SymbolicError::Intcode(IntcodeError::AddressOverflow {
+                program_counter: self.program_counter,
+                instruction,
+                relative_base: self.relative_base,
+                offset,
+            }))?;
+
+        self.address(address, instruction)
+    }
+
+    // Resolves the nth argument of the current instruction as a value
+    fn input(
+        &self,
+        decoded: &Decoded,
+        value: Value,
+        nth: usize,
+    ) -> Result<Rc<Expr>, SymbolicError> {
+        let operand = self.read(self.program_counter + 1 + nth);
+        let relative = match self.mode(decoded, value, nth)? {
+            ArgumentMode::Immediate => return Ok(operand),
+            ArgumentMode::Indexed => false,
+            ArgumentMode::Relative => true,
+        };
+
+        match operand.constant() {
+            Some(operand) if relative => Ok(self.read(self.relative(operand, value)?)),
+            Some(operand) => Ok(self.read(self.address(operand, value)?)),
+            // The read itself is fine, but what it reads cannot be known
+            None if relative => Ok(Rc::new(Expr::Load(Expr::add(
+                Rc::new(Expr::Const(self.relative_base as Value)),
+                operand,
+            )))),
+            None => Ok(Rc::new(Expr::Load(operand))),
+        }
+    }
+
+    // Resolves the nth argument of the current instruction as an address to write to
+    fn output(
+        &self,
+        decoded: &Decoded,
+        value: Value,
+        nth: usize,
+    ) -> Result<Address, SymbolicError> {
+        let operand = self.concrete(
+            self.program_counter + 1 + nth,
+            SymbolicError::SymbolicWrite {
+                program_counter: self.program_counter,
+            },
+        )?;
+
+        match self.mode(decoded, value, nth)? {
+            ArgumentMode::Immediate => Err(SymbolicError::Intcode(IntcodeError::ImmediateWrite {
+                program_counter: self.program_counter,
+                instruction: value,
+                argument: nth,
+            })),
+            ArgumentMode::Indexed => self.address(operand, value),
+            ArgumentMode::Relative => self.relative(operand, value),
+        }
+    }
+
+    fn mode(
+        &self,
+        decoded: &Decoded,
+        value: Value,
+        nth: usize,
+    ) -> Result<ArgumentMode, SymbolicError> {
+        decoded.modes[nth].map_err(|mode| {
+            SymbolicError::Intcode(IntcodeError::InvalidMode {
+                program_counter: self.program_counter,
+                instruction: value,
+                argument: nth,
+                mode,
+            })
+        })
+    }
+
+    // Executes a single instruction, returning whether the program is still running
+    fn step(&mut self) -> Result<bool, SymbolicError> {
+        use Instruction::*;
+
+        let program_counter = self.program_counter;
+        let value = self.concrete(
+            program_counter,
+            SymbolicError::SymbolicInstruction { program_counter },
+        )?;
+        let decoded = Decoded::new(value).map_err(|instruction| {
+            SymbolicError::Intcode(IntcodeError::UnknownOpcode {
+                program_counter,
+                instruction,
+            })
+        })?;
+        let next = program_counter + 1 + decoded.argument_types.len();
+
+        match decoded.instruction {
+            Add | Mul | LessThan | Equals => {
+                let lhs = self.input(&decoded, value, 0)?;
+                let rhs = self.input(&decoded, value, 1)?;
+                let destination = self.output(&decoded, value, 2)?;
+
+                let result = match decoded.instruction {
+                    Add => Expr::add(lhs, rhs),
+                    Mul => Expr::mul(lhs, rhs),
+                    LessThan => Expr::less_than(lhs, rhs),
+                    _ => Expr::equals(lhs, rhs),
+                };
+
+                self.memory.insert(destination, result);
+            }
+            Input => {
+                let destination = self.output(&decoded, value, 0)?;
+
+                self.memory.insert(
+                    destination,
+                    Rc::new(Expr::Symbol(Symbol::Input(self.inputs))),
+                );
+                self.inputs += 1;
+            }
+            Output => {
+                let output = self.input(&decoded, value, 0)?;
+                self.outputs.push(output);
+            }
+            JumpIfTrue | JumpIfFalse => {
+                let error = SymbolicError::SymbolicJump { program_counter };
+                let condition = self
+                    .input(&decoded, value, 0)?
+                    .constant()
+                    .ok_or(error.clone())?;
+                let destination = self.input(&decoded, value, 1)?.constant().ok_or(error)?;
+
+                if (condition != 0) == (decoded.instruction == JumpIfTrue) {
+                    self.program_counter = self.address(destination, value)?;
+                    return Ok(true);
+                }
+            }
+            RelativeBaseOffset => {
+                let change = self
+                    .input(&decoded, value, 0)?
+                    .constant()
+                    .ok_or(SymbolicError::SymbolicRelativeBase { program_counter })?;
+
+                self.relative_base = Value::try_from(self.relative_base)
+                    .ok()
+                    .and_then(|relative_base| relative_base.checked_add(change))
+                    .and_then(|relative_base| Address::try_from(relative_base).ok())
+                    .ok_or(SymbolicError::Intcode(
+                        IntcodeError::RelativeBaseUnderflow {
+                            program_counter,
+                            instruction: value,
+                            relative_base: self.relative_base,
+                            change,
+                        },
+                    ))?;
+            }
+            Halt => return Ok(false),
+            Extension(_) => unreachable!("Extensions are never decoded here"),
+        }
+
+        self.program_counter = next;
+        Ok(true)
+    }
+}
+
+// A symbol to solve for, along with the values it may take
+#[derive(Debug, Clone, PartialEq)]
+pub struct Unknown {
+    pub symbol: Symbol,
+    pub values: RangeInclusive<Value>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Method {
+    Affine,
+    BruteForce,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Solution {
+    // One per unknown, in the same order
+    pub values: Vec<Value>,
+    pub method: Method,
+}
+
+// Number of values in a range, if it can be counted
+fn size(values: &RangeInclusive<Value>) -> Option<usize> {
+    let size = values.end().checked_sub(*values.start())?.checked_add(1)?;
+    usize::try_from(size).ok()
+}
+
+// Number of combinations of values the unknowns can take, if it can be counted
+fn combinations(unknowns: &[Unknown]) -> Option<usize> {
+    unknowns.iter().try_fold(1_usize, |combinations, unknown| {
+        combinations.checked_mul(size(&unknown.values)?)
+    })
+}
+
+// The nth combination of values, with the last unknown changing fastest. The unknowns' ranges must
+// have been checked by solve.
+fn combination(unknowns: &[Unknown], mut index: usize) -> Vec<Value> {
+    let mut values = vec![0; unknowns.len()];
+
+    for (value, unknown) in values.iter_mut().zip(unknowns).rev() {
+        let size = size(&unknown.values).unwrap();
+        *value = unknown.values.start() + Value::try_from(index % size).unwrap();
+        index /= size;
+    }
+
+    values
+}
+
+// Runs a program with concrete values for the unknowns, returning what ends up at an address. Runs
+// that fail or do not halt have no result.
+fn evaluate(
+    program: &[Value],
+    unknowns: &[Unknown],
+    values: &[Value],
+    address: Address,
+) -> Option<Value> {
+    let mut computer = Computer::new(program.to_vec(), None);
+    let mut inputs = BTreeMap::new();
+
+    for (unknown, &value) in unknowns.iter().zip(values) {
+        match unknown.symbol {
+            Symbol::Cell(cell) => computer.patch(cell, value),
+            Symbol::Input(nth) => {
+                inputs.insert(nth, value);
+            }
+        }
+    }
+
+    for value in inputs.values() {
+        computer.provide_input(*value);
+    }
+
+    for _ in 0..STEP_LIMIT {
+        if computer.step().ok()? == Instruction::Halt {
+            return Some(computer.read(address));
+        }
+
+        // Outputs are of no interest
+        while computer.hardware.pop_output().is_some() {}
+    }
+
+    None
+}
+
+// The affine solver could not compute a value without overflowing, so its answer cannot be trusted
+#[derive(Debug)]
+struct Overflow;
+
+// Finds the first combination (in the order the unknowns are listed) for which the affine
+// expression equals the target. All but the last unknown with a non-zero coefficient are tried,
+// and the value of that one is computed from them.
+fn solve_affine(
+    affine: &Affine,
+    unknowns: &[Unknown],
+    target: Value,
+) -> Result<Option<Vec<Value>>, Overflow> {
+    let solved = match unknowns
+        .iter()
+        .rposition(|unknown| affine.coefficient(unknown.symbol) != 0)
+    {
+        Some(solved) => solved,
+        // Nothing the unknowns take changes the result
+        None if affine.constant == target => return Ok(Some(combination(unknowns, 0))),
+        None => return Ok(None),
+    };
+
+    let (before, after) = (&unknowns[..solved], &unknowns[solved + 1..]);
+    let coefficient = affine.coefficient(unknowns[solved].symbol);
+
+    for index in 0..combinations(before).unwrap() {
+        let mut values = combination(before, index);
+
+        let mut rest = target.checked_sub(affine.constant).ok_or(Overflow)?;
+        for (unknown, value) in before.iter().zip(&values) {
+            let term = affine
+                .coefficient(unknown.symbol)
+                .checked_mul(*value)
+                .ok_or(Overflow)?;
+            rest = rest.checked_sub(term).ok_or(Overflow)?;
+        }
+
+        // Only Value::MIN / -1 can overflow here
+        let value = rest.checked_div(coefficient).ok_or(Overflow)?;
+        if rest.checked_rem(coefficient) != Some(0) || !unknowns[solved].values.contains(&value) {
+            continue;
+        }
+
+        values.push(value);
+        values.extend(combination(after, 0));
+        return Ok(Some(values));
+    }
+
+    Ok(None)
+}
+
+// Tries every combination on a number of workers, returning the first one (in the order the
+// unknowns are listed) that works
+fn brute_force(
+    program: &[Value],
+    unknowns: &[Unknown],
+    address: Address,
+    target: Value,
+    workers: usize,
+) -> Option<Vec<Value>> {
+    let total = combinations(unknowns).unwrap();
+    let next = AtomicUsize::new(0);
+    let found = AtomicUsize::new(usize::MAX);
+
+    thread::scope(|scope| {
+        for _ in 0..workers.max(1) {
+            scope.spawn(|| loop {
+                let start = next.fetch_add(CHUNK, Ordering::Relaxed);
+
+                // Chunks after a known solution cannot hold the first one
+                if start >= total || start > found.load(Ordering::Relaxed) {
+                    break;
+                }
+
+                for index in start..total.min(start + CHUNK) {
+                    let values = combination(unknowns, index);
+
+                    if evaluate(program, unknowns, &values, address) == Some(target) {
+                        found.fetch_min(index, Ordering::Relaxed);
+                        break;
+                    }
+                }
+            });
+        }
+    });
+
+    match found.into_inner() {
+        usize::MAX => None,
+        index => Some(combination(unknowns, index)),
+    }
+}
+
+// Finds values for the unknowns that make a program halt with the target value at an address. The
+// program is first run symbolically: if the result is an affine function of the unknowns it is
+// solved directly, and otherwise every combination is tried. Inputs must be numbered from zero.
+// Fails if an unknown has no values to take, or if the combinations cannot be counted.
+pub fn solve(
+    program: &[Value],
+    unknowns: &[Unknown],
+    address: Address,
+    target: Value,
+    workers: usize,
+) -> Result<Option<Solution>, SymbolicError> {
+    if let Some(unknown) = unknowns
+        .iter()
+        .position(|unknown| unknown.values.is_empty())
+    {
+        return Err(SymbolicError::EmptyRange { unknown });
+    }
+    if combinations(unknowns).is_none() {
+        return Err(SymbolicError::TooManyCombinations);
+    }
+
+    let mut machine = SymbolicMachine::new(program);
+    for unknown in unknowns {
+        if let Symbol::Cell(cell) = unknown.symbol {
+            machine.make_symbolic(cell);
+        }
+    }
+
+    let affine = machine
+        .run()
+        .ok()
+        .and_then(|_| machine.read(address).affine())
+        .filter(|affine| {
+            // Symbols that are not unknowns (i.e. inputs no value is given for) cannot be solved for
+            affine
+                .coefficients
+                .keys()
+                .all(|symbol| unknowns.iter().any(|unknown| unknown.symbol == *symbol))
+        });
+
+    // On overflow the affine solution is not trusted and the combinations are tried instead
+    if let Some(Ok(values)) = affine.map(|affine| solve_affine(&affine, unknowns, target)) {
+        // Reads through symbolic addresses are assumed not to fail, which is checked here
+        let verified = values
+            .as_ref()
+            .is_none_or(|values| evaluate(program, unknowns, values, address) == Some(target));
+
+        if verified {
+            return Ok(values.map(|values| Solution {
+                values,
+                method: Method::Affine,
+            }));
+        }
+    }
+
+    Ok(
+        brute_force(program, unknowns, address, target, workers).map(|values| Solution {
+            values,
+            method: Method::BruteForce,
+        }),
+    )
+}
+
+#[cfg(test)]
+mod solving {
+    use super::*;
+
+    fn cells(cells: &[Address]) -> Vec<Unknown> {
+        cells
+            .iter()
+            .map(|&cell| Unknown {
+                symbol: Symbol::Cell(cell),
+                values: 0..=99,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn expressions() {
+        // [0] = [1] * 3 + [2], after a read through [1] that is overwritten
+        let program = vec![1, 0, 0, 3, 102, 3, 1, 3, 1, 3, 2, 0, 99];
+        let mut machine = SymbolicMachine::new(&program);
+        machine.make_symbolic(1);
+        machine.make_symbolic(2);
+
+        assert_eq!(machine.run(), Ok(()));
+        assert_eq!(machine.read(0).to_string(), "((3 * [1]) + [2])");
+        assert_eq!(
+            machine.read(0).affine(),
+            Some(Affine {
+                constant: 0,
+                coefficients: vec![(Symbol::Cell(1), 3), (Symbol::Cell(2), 1)]
+                    .into_iter()
+                    .collect()
+            })
+        );
+    }
+
+    #[test]
+    fn inputs_and_outputs() {
+        let program = vec![3, 9, 1001, 9, 5, 10, 4, 10, 99, 0, 0];
+        let mut machine = SymbolicMachine::new(&program);
+
+        assert_eq!(machine.run(), Ok(()));
+        assert_eq!(machine.outputs()[0].to_string(), "(in0 + 5)");
+    }
+
+    #[test]
+    fn symbolic_control_flow() {
+        let mut machine = SymbolicMachine::new(&[3, 5, 1005, 5, 0, 0]);
+
+        assert_eq!(
+            machine.run(),
+            Err(SymbolicError::SymbolicJump { program_counter: 2 })
+        );
+    }
+
+    #[test]
+    fn affine_solution() {
+        let program = vec![1, 0, 0, 3, 102, 3, 1, 3, 1, 3, 2, 0, 99];
+
+        assert_eq!(
+            solve(&program, &cells(&[1, 2]), 0, 100, 2),
+            Ok(Some(Solution {
+                values: vec![1, 97],
+                method: Method::Affine
+            }))
+        );
+        assert_eq!(solve(&program, &cells(&[1, 2]), 0, 1000, 2), Ok(None));
+    }
+
+    #[test]
+    fn brute_force_fallback() {
+        // [0] = [5] * [6], which is not affine
+        let program = vec![2, 5, 6, 0, 99, 0, 0];
+
+        assert_eq!(
+            solve(&program, &cells(&[5, 6]), 0, 12, 4),
+            Ok(Some(Solution {
+                values: vec![1, 12],
+                method: Method::BruteForce
+            }))
+        );
+    }
+
+    #[test]
+    fn overflowing_constants() {
+        // [0] = MAX + 1, which is left as it is rather than folded
+        let mut machine = SymbolicMachine::new(&[1101, Value::MAX, 1, 0, 99]);
+
+        assert_eq!(machine.run(), Ok(()));
+        assert_eq!(machine.read(0).to_string(), "(9223372036854775807 + 1)");
+        assert_eq!(machine.read(0).affine(), None);
+    }
+
+    #[test]
+    fn overflow_fallback() {
+        // [0] = [13] * 2^40 * 2^40 + [14], whose coefficient for [13] overflows
+        let program = vec![
+            1002,
+            13,
+            1 << 40,
+            0,
+            1002,
+            0,
+            1 << 40,
+            0,
+            1,
+            0,
+            14,
+            0,
+            99,
+            0,
+            0,
+        ];
+
+        assert_eq!(
+            solve(&program, &cells(&[13, 14]), 0, 5, 1),
+            Ok(Some(Solution {
+                values: vec![0, 5],
+                method: Method::BruteForce
+            }))
+        );
+
+        // [0] = MIN + [5], which can only be solved for by overflowing
+        let program = vec![101, Value::MIN, 5, 0, 99, 0];
+        assert_eq!(solve(&program, &cells(&[5]), 0, 5, 1), Ok(None));
+    }
+
+    #[test]
+    fn solving_for_inputs() {
+        // Outputs whether the input is less than 7, and halts with twice the input at [0]
+        let program = vec![3, 13, 1007, 13, 7, 14, 4, 14, 1002, 13, 2, 0, 99, 0, 0];
+        let unknowns = vec![Unknown {
+            symbol: Symbol::Input(0),
+            values: -10..=10,
+        }];
+
+        assert_eq!(
+            solve(&program, &unknowns, 0, -8, 1),
+            Ok(Some(Solution {
+                values: vec![-4],
+                method: Method::Affine
+            }))
+        );
+    }
+
+    #[test]
+    #[allow(clippy::reversed_empty_ranges)]
+    fn invalid_ranges() {
+        let program = vec![1, 0, 0, 3, 102, 3, 1, 3, 1, 3, 2, 0, 99];
+        let mut unknowns = cells(&[1, 2]);
+
+        unknowns[1].values = 5..=4;
+        assert_eq!(
+            solve(&program, &unknowns, 0, 100, 2),
+            Err(SymbolicError::EmptyRange { unknown: 1 })
+        );
+
+        unknowns[1].values = Value::MIN..=Value::MAX;
+        assert_eq!(
+            solve(&program, &unknowns, 0, 100, 2),
+            Err(SymbolicError::TooManyCombinations)
+        );
+
+        // Each range can be counted, but not all combinations of them
+        unknowns[0].values = 0..=Value::MAX / 2;
+        unknowns[1].values = 0..=Value::MAX / 2;
+        assert_eq!(
+            solve(&program, &unknowns, 0, 100, 2),
+            Err(SymbolicError::TooManyCombinations)
+        );
+    }
+}
@@ -0,0 +1,254 @@
+use std::collections::VecDeque;
+use std::io::{self, BufRead, Write};
+
+use serde::{Deserialize, Serialize};
+
+use super::*;
+
+// Everything that happened while executing a single instruction
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Step {
+    pub program_counter: Address,
+    pub instruction: Value,
+    pub arguments: Vec<Argument>,
+    pub writes: Vec<(Address, Value)>,
+    pub input: Option<Value>,
+    pub output: Option<Value>,
+}
+
+// Receives each instruction executed by a computer
+pub trait Tracer {
+    fn trace(&mut self, step: &Step);
+}
+
+// Traces can be kept in memory
+impl Tracer for Vec<Step> {
+    fn trace(&mut self, step: &Step) {
+        self.push(step.clone());
+    }
+}
+
+// Writes traces as JSON lines, one per step. Since tracing cannot fail, the first error is kept
+// and returned once writing is finished.
+pub struct TraceWriter<W: Write> {
+    writer: W,
+    error: Option<io::Error>,
+}
+
+impl<W: Write> TraceWriter<W> {
+    pub fn new(writer: W) -> TraceWriter<W> {
+        TraceWriter {
+            writer,
+            error: None,
+        }
+    }
+
+    pub fn finish(mut self) -> io::Result<W> {
+        match self.error {
+            Some(error) => Err(error),
+            None => self.writer.flush().map(|_| self.writer),
+        }
+    }
+}
+
+impl<W: Write> Tracer for TraceWriter<W> {
+    fn trace(&mut self, step: &Step) {
+        if self.error.is_some() {
+            return;
+        }
+
+        let result = serde_json::to_writer(&mut self.writer, step)
+            .map_err(io::Error::from)
+            .and_then(|_| self.writer.write_all(b"\n"));
+
+        self.error = result.err();
+    }
+}
+
+pub fn read_trace<R: BufRead>(reader: R) -> io::Result<Vec<Step>> {
+    reader
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| line.as_ref().map_or(true, |line| !line.trim().is_empty()))
+        .map(|(index, line)| {
+            serde_json::from_str(&line?).map_err(|error| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("line {}: {}", index + 1, error),
+                )
+            })
+        })
+        .collect()
+}
+
+// Feeds the inputs of a recorded trace back to a computer, keeping track of the outputs it produces
+pub struct Replay {
+    inputs: VecDeque<Value>,
+    pub outputs: Vec<Value>,
+}
+
+impl Replay {
+    pub fn new(trace: &[Step]) -> Replay {
+        Replay {
+            inputs: trace.iter().filter_map(|step| step.input).collect(),
+            outputs: Vec::new(),
+        }
+    }
+}
+
+impl IO for Replay {
+    fn input(&mut self) -> Option<Value> {
+        self.inputs.pop_front()
+    }
+
+    fn output(&mut self, value: Value) {
+        self.outputs.push(value);
+    }
+}
+
+// Runs a program with the inputs of a recorded trace, returning the trace of the new run. If the
+// program fails, the steps executed until then are returned along with the error.
+pub fn replay(program: Vec<Value>, trace: &[Step]) -> (Vec<Step>, Result<(), IntcodeError>) {
+    let mut replay = Replay::new(trace);
+    let mut steps = Vec::new();
+
+    let result = {
+        let mut computer = Computer::new(program, Some(&mut replay));
+        computer.set_tracer(&mut steps);
+        computer.run()
+    };
+
+    (steps, result)
+}
+
+// The first point at which two traces differ. A missing step means that trace ended earlier.
+#[derive(Debug, PartialEq)]
+pub struct Divergence<'t> {
+    pub index: usize,
+    pub left: Option<&'t Step>,
+    pub right: Option<&'t Step>,
+}
+
+pub fn diff<'t>(left: &'t [Step], right: &'t [Step]) -> Option<Divergence<'t>> {
+    (0..std::cmp::max(left.len(), right.len()))
+        .map(|index| Divergence {
+            index,
+            left: left.get(index),
+            right: right.get(index),
+        })
+        .find(|divergence| divergence.left != divergence.right)
+}
+
+#[cfg(test)]
+mod tracing {
+    use super::*;
+
+    struct FixedIO(Value);
+
+    impl IO for FixedIO {
+        fn input(&mut self) -> Option<Value> {
+            Some(self.0)
+        }
+
+        fn output(&mut self, _: Value) {}
+    }
+
+    fn record(program: Vec<Value>, input: Value) -> Vec<Step> {
+        let mut io = FixedIO(input);
+        let mut steps = Vec::new();
+
+        let mut computer = Computer::new(program, Some(&mut io));
+        computer.set_tracer(&mut steps);
+        computer.run().unwrap();
+
+        steps
+    }
+
+    #[test]
+    fn steps() {
+        let steps = record(vec![3, 0, 1001, 0, 2, 0, 4, 0, 99], 5);
+
+        assert_eq!(
+            steps,
+            vec![
+                Step {
+                    program_counter: 0,
+                    instruction: 3,
+                    arguments: vec![Argument::Out(0)],
+                    writes: vec![(0, 5)],
+                    input: Some(5),
+                    output: None,
+                },
+                Step {
+                    program_counter: 2,
+                    instruction: 1001,
+                    arguments: vec![Argument::In(5), Argument::In(2), Argument::Out(0)],
+                    writes: vec![(0, 7)],
+                    input: None,
+                    output: None,
+                },
+                Step {
+                    program_counter: 6,
+                    instruction: 4,
+                    arguments: vec![Argument::In(7)],
+                    writes: vec![],
+                    input: None,
+                    output: Some(7),
+                },
+                Step {
+                    program_counter: 8,
+                    instruction: 99,
+                    arguments: vec![],
+                    writes: vec![],
+                    input: None,
+                    output: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn json_lines() {
+        let steps = record(vec![3, 0, 4, 0, 99], 42);
+
+        let mut writer = TraceWriter::new(Vec::new());
+        for step in &steps {
+            writer.trace(step);
+        }
+        let json = writer.finish().unwrap();
+
+        assert_eq!(
+            String::from_utf8(json.clone()).unwrap().lines().next(),
+            Some(
+                r#"{"program_counter":0,"instruction":3,"arguments":[{"Out":0}],"writes":[[0,42]],"input":42,"output":null}"#
+            )
+        );
+        assert_eq!(read_trace(&json[..]).unwrap(), steps);
+    }
+
+    #[test]
+    fn replay_reproduces_run() {
+        // Test if input equals 8
+        let program = vec![3, 9, 8, 9, 10, 9, 4, 9, 99, -1, 8];
+        let steps = record(program.clone(), 8);
+
+        let (replayed, result) = replay(program.clone(), &steps);
+        assert_eq!(result, Ok(()));
+        assert_eq!(diff(&steps, &replayed), None);
+
+        let other = record(program, 7);
+        let divergence = diff(&steps, &other).unwrap();
+        assert_eq!(divergence.index, 0);
+        assert_eq!(divergence.left.unwrap().input, Some(8));
+        assert_eq!(divergence.right.unwrap().input, Some(7));
+    }
+
+    #[test]
+    fn diff_lengths() {
+        let steps = record(vec![104, 1, 99], 0);
+
+        let divergence = diff(&steps, &steps[..1]).unwrap();
+        assert_eq!(divergence.index, 1);
+        assert_eq!(divergence.right, None);
+    }
+}